@@ -0,0 +1,326 @@
+// Fetching solution logs from the code.golf API, with retries and an
+// on-disk cache.
+
+use std::fmt;
+use std::error::Error;
+use chrono::{TimeDelta, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::model::*;
+use crate::render::sanitize;
+
+// One client does for every request, so that connections get reused. The
+// timeout covers the whole request, body included, so a stalled connection
+// can't hang the run. Solution logs compress very well, so responses are
+// asked for gzipped (or deflated) and decompressed as they come in.
+
+pub fn build_client(timeout_secs: u64) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .gzip(true)
+        .deflate(true)
+        .build()
+}
+
+// Where the API lives, unless --api-base says otherwise.
+
+pub const API_BASE: &str = "https://code.golf";
+
+// The holes and languages lists. These are small, so unlike the solution
+// logs, they don't get retried. The holes list hardly ever changes, though,
+// so it's cached (for ttl, if given; otherwise it's fetched fresh and the
+// cache updated).
+
+pub async fn get_holes_text(client: &reqwest::Client, api_base: &str, ttl: Option<TimeDelta>) -> Result<String, Box<dyn Error>> {
+    let cache_path = (api_base == API_BASE).then(|| dirs::cache_dir().map(|dir| dir.join("holier-than-thou").join("holes.json"))).flatten();
+
+    if let Some(text) = cache_path.as_ref().zip(ttl).and_then(|(path, ttl)| read_cache(path, ttl))
+    && serde_json::from_str::<Vec<Hole>>(&text).is_ok() {
+        tracing::info!("reused the cached holes list");
+        return Ok(text);
+    }
+
+    let text = client.get(format!("{api_base}/api/holes")).send().await?.error_for_status()?.text().await?;
+
+    if let Some(path) = &cache_path && serde_json::from_str::<Vec<Hole>>(&text).is_ok() {
+        write_cache(path, &text);
+    }
+
+    Ok(text)
+}
+
+pub async fn get_holes(client: &reqwest::Client, api_base: &str) -> Result<Vec<Hole>, Box<dyn Error>> {
+    Ok(serde_json::from_str(&get_holes_text(client, api_base, None).await?)?)
+}
+
+pub async fn get_langs(client: &reqwest::Client, api_base: &str) -> Result<Vec<Lang>, Box<dyn Error>> {
+    let text = client.get(format!("{api_base}/api/langs")).send().await?.error_for_status()?.text().await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+// How to go about fetching solution logs.
+
+pub struct FetchOptions {
+    pub api_base: String,                   // Without a trailing slash.
+    pub clean_api: bool,
+    pub retry_on_empty: bool,
+    pub max_retries: u32,
+    pub retry_base_ms: u64,
+    pub scores_query: Vec<(String, String)>,
+    pub cache_ttl: Option<TimeDelta>,       // None with --no-cache.
+    pub dump: Option<std::path::PathBuf>,   // Where --dump saves logs to.
+}
+
+pub fn solution_log_url(options: &FetchOptions, lang: &str, hole_id: &str) -> String {
+    if options.clean_api {
+        let query: Vec<String> = options.scores_query.iter().map(|(key, value)|
+            format!("{}={}", urlencoding::encode(key), urlencoding::encode(value))
+        ).collect();
+
+        format!(
+            "{}/scores/{}/{}/all{}{}",
+            options.api_base,
+            urlencoding::encode(hole_id),
+            urlencoding::encode(lang),
+            if query.is_empty() {""} else {"?"},
+            query.join("&"),
+        )
+    } else {
+        format!(
+            "{}/api/solutions-log?hole={}&lang={}",
+            options.api_base,
+            urlencoding::encode(hole_id),
+            urlencoding::encode(lang),
+        )
+    }
+}
+
+#[tracing::instrument(skip_all, fields(hole = hole_id, lang = lang))]
+pub async fn get_solution_log(client: &reqwest::Client, options: &FetchOptions, lang: &str, hole_id: &str) -> Result<Vec<Solution>, FetchError> {
+    let url = solution_log_url(options, lang, hole_id);
+
+    // A log that was fetched recently enough is reused from the cache.
+
+    let cache_path = options.cache_ttl.and_then(|_| cache_path(options, lang, hole_id));
+
+    if let Some(text) = cache_path.as_ref().zip(options.cache_ttl).and_then(|(path, ttl)| read_cache(path, ttl))
+    && let Ok(ret) = parse_solution_log(&text) {
+        tracing::info!(solutions = ret.len(), "reused the cached log");
+        dump(options, hole_id, &text)?;
+        return Ok(ret);
+    }
+
+    // The API sometimes hands back an empty log for a hole that does have
+    // solutions (seemingly during deploys). Some holes legitimately have no
+    // solutions in a given language, though, so with --retry-on-empty we
+    // only re-ask a couple of times before believing it.
+
+    let empty_retries = if options.retry_on_empty {2} else {0};
+    let mut empty_results = 0;
+    let mut failure = None;
+
+    for attempt in 0..=options.max_retries {
+        if attempt > 0 {
+            match &failure {
+                Some(err) => tracing::debug!(attempt, error = %err, "retrying"),
+                None      => tracing::debug!(attempt, "got an empty log, retrying"),
+            }
+
+            tokio::time::sleep(retry_delay(options.retry_base_ms, attempt)).await;
+        }
+
+        tracing::trace!(attempt, url, "requesting");
+
+        let resp = match client.get(&url).send().await {
+            Ok(resp) => resp,
+            Err(err) => {failure = Some(FetchError::from_reqwest(err, attempt + 1)); continue;}
+        };
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            failure = Some(FetchError::Status(status, attempt + 1));
+            continue;
+        }
+
+        let is_html = resp.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/html"));
+
+        let text = match resp.text().await {
+            Ok(text) => text,
+            Err(err) => {failure = Some(FetchError::from_reqwest(err, attempt + 1)); continue;}
+        };
+
+        // During outages, the site can answer with a 200 and an HTML error
+        // page, which is worth waiting out like any other failure.
+
+        if is_html || text.trim_start().starts_with('<') {
+            let snippet: String = text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(200).collect();
+            failure = Some(FetchError::Html(sanitize(&snippet)));
+            continue;
+        }
+
+        // A log that doesn't parse won't parse any better the next time,
+        // so there's no point retrying.
+
+        let ret = parse_solution_log(&text).map_err(FetchError::Parse)?;
+
+        if ret.is_empty() && empty_results < empty_retries {
+            empty_results += 1;
+            failure = None;
+            continue;
+        }
+
+        // An empty log might just be the API having a moment, so it's not
+        // worth holding onto.
+
+        if let Some(path) = &cache_path && !ret.is_empty() {
+            write_cache(path, &text);
+        }
+
+        tracing::info!(%status, attempts = attempt + 1, solutions = ret.len(), "fetched the log");
+
+        dump(options, hole_id, &text)?;
+        return Ok(ret);
+    }
+
+    match failure {
+        Some(err) => {
+            tracing::warn!(error = %err, "giving up");
+            Err(err)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+// With --dump, save each solution log as it came from the API, where
+// --offline can read it back from.
+
+fn dump(options: &FetchOptions, hole_id: &str, text: &str) -> Result<(), FetchError> {
+    match &options.dump {
+        Some(dir) => std::fs::write(dir.join(format!("{hole_id}.json")), text).map_err(FetchError::Dump),
+        None => Ok(()),
+    }
+}
+
+// However a solution log was gotten (from the API, the cache, or a file),
+// it's parsed the same way.
+
+pub fn parse_solution_log(text: &str) -> Result<Vec<Solution>, serde_json::Error> {
+    let mut ret: Vec<Solution> = serde_json::from_str(text)?;
+    fix_up_dates(&mut ret);
+    Ok(ret)
+}
+
+// With --offline, each hole's solution log is read from <dir>/<hole>.json
+// instead (like the ones --dump writes). A hole with no file there just
+// has no solutions.
+
+pub fn read_offline_log(dir: &std::path::Path, hole_id: &str) -> Result<Vec<Solution>, Box<dyn Error>> {
+    match std::fs::read_to_string(dir.join(format!("{hole_id}.json"))) {
+        Ok(text) => Ok(parse_solution_log(&text)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// The ways fetching a solution log can fail. The code.golf API is a little
+// unstable, so all but the last two are usually worth just re-running for.
+// (The very last is a problem on our end, saving the log with --dump.)
+
+#[derive(Debug)]
+pub enum FetchError {
+    Network(reqwest::Error),
+    Timeout(u32),
+    Status(reqwest::StatusCode, u32),
+    Html(String),
+    Parse(serde_json::Error),
+    Dump(std::io::Error),
+}
+
+impl FetchError {
+    pub fn from_reqwest(err: reqwest::Error, attempts: u32) -> FetchError {
+        if err.is_timeout() {FetchError::Timeout(attempts)} else {FetchError::Network(err)}
+    }
+
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, FetchError::Parse(_) | FetchError::Dump(_))
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Network(err)           => write!(f, "couldn't reach the code.golf API ({err})"),
+            FetchError::Timeout(attempts)      => write!(f, "the code.golf API didn't answer in time for {attempts} attempts in a row"),
+            FetchError::Status(code, attempts) => write!(f, "the code.golf API gave a {code} status code for {attempts} attempts in a row"),
+            FetchError::Html(snippet)          => write!(f, "the code.golf API sent an HTML page instead of a solution log, starting: {snippet}"),
+            FetchError::Parse(err)             => write!(f, "the code.golf API sent a solution log that couldn't be parsed ({err})"),
+            FetchError::Dump(err)              => write!(f, "couldn't save it to the --dump directory ({err})"),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+// Solution logs are cached under the OS's cache directory, one file per
+// hole, language, and endpoint. Logs fetched with --scores-query aren't
+// cached, since the extra parameters could change the response, and
+// neither are ones from an --api-base other than code.golf itself.
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    pub fetched_at: i64,                // Unix timestamp.
+    pub body: String,                   // The response, as is.
+}
+
+fn cache_path(options: &FetchOptions, lang: &str, hole_id: &str) -> Option<std::path::PathBuf> {
+    if !options.scores_query.is_empty() || options.api_base != API_BASE {
+        return None;
+    }
+
+    let endpoint = if options.clean_api {"scores"} else {"log"};
+
+    Some(dirs::cache_dir()?
+        .join("holier-than-thou")
+        .join(urlencoding::encode(lang).as_ref())
+        .join(format!("{}.{endpoint}.json", urlencoding::encode(hole_id))))
+}
+
+fn read_cache(path: &std::path::Path, ttl: TimeDelta) -> Option<String> {
+    let entry: CacheEntry = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    let age = Utc::now().timestamp() - entry.fetched_at;
+
+    (age >= 0 && age < ttl.num_seconds()).then_some(entry.body)
+}
+
+// Failing to write the cache just means fetching again next time, so
+// errors are ignored.
+
+fn write_cache(path: &std::path::Path, body: &str) {
+    let entry = CacheEntry {fetched_at: Utc::now().timestamp(), body: body.to_string()};
+
+    if let (Some(dir), Ok(json)) = (path.parent(), serde_json::to_string(&entry)) {
+        std::fs::create_dir_all(dir).and_then(|_| std::fs::write(path, json)).ok();
+    }
+}
+
+// Exponential backoff between retries: the base delay, doubled for each
+// retry after the first and capped at a few seconds, plus up to a quarter
+// more at random so the concurrent fetches don't all retry in lockstep.
+
+fn retry_delay(base_ms: u64, attempt: u32) -> std::time::Duration {
+    let delay_ms = base_ms.saturating_mul(1 << (attempt - 1).min(16)).min(5000);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.subsec_nanos() as u64)
+        .unwrap_or(0);
+
+    let jitter_ms = nanos % (delay_ms / 4 + 1);
+
+    std::time::Duration::from_millis(delay_ms + jitter_ms)
+}
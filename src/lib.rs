@@ -0,0 +1,8 @@
+// The fetching, processing, and formatting behind the holier-than-thou
+// command, split out so that it can be driven (and tested) without the
+// command line or the network.
+
+pub mod api;
+pub mod model;
+pub mod process;
+pub mod render;
@@ -1,164 +1,1282 @@
 
-use std::fmt;
+use std::cmp::Ordering;
 use std::error::Error;
-use serde::{Serialize, Deserialize};
-use regex::Regex;
-use clap::Parser;
-use chrono::{Utc};
-
-const BOLD:     &'static str = "\x1b[1m";
-const DIM:      &'static str = "\x1b[2m";
-const ULINE:    &'static str = "\x1b[4m";
-const GREEN:    &'static str = "\x1b[32m";
-const RED:      &'static str = "\x1b[31m";
-const YELLOW:   &'static str = "\x1b[33m";
-const BROWN:    &'static str = "\x1b[38;5;130m";
-const BLUE:     &'static str = "\x1b[36m";
-const GREY:     &'static str = "\x1b[38;5;236m";
-const MGREY:    &'static str = "\x1b[38;5;238m";
-const LGREY:    &'static str = "\x1b[38;5;240m";
-const MLGREY:   &'static str = "\x1b[38;5;242m";
-const LLGREY:   &'static str = "\x1b[38;5;244m";
-const LLLGREY:  &'static str = "\x1b[38;5;252m";
-const LLLLGREY: &'static str = "\x1b[38;5;254m";
-const RESET:    &'static str = "\x1b[0m";
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Hole {
-    category: String,
-    id: String,
-    name: String,
-    preamble: String,
-    links: Vec<HoleLink>,
-}
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
+use std::collections::{HashMap, HashSet};
+use regex::RegexBuilder;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures_util::StreamExt;
+use chrono::{TimeDelta, Utc};
+use unicode_width::UnicodeWidthStr;
+
+use holier_than_thou::api::*;
+use holier_than_thou::model::*;
+use holier_than_thou::process::*;
+use holier_than_thou::render::*;
+
+// The scoring methods code.golf has leaderboards for, plus "both" for a
+// bytes report with a chars line under each hole, and "points" for a bytes
+// report that also gives each hole's points delta and both points totals.
+
+const SCORINGS: [&str; 4] = ["bytes", "chars", "both", "points"];
+
+// A small made-up dataset for --demo, in the same shape as the API's
+// /api/holes and solutions-log responses (the latter keyed by hole ID).
+
+const DEMO_HOLES:     &str = include_str!("demo/holes.json");
+const DEMO_SOLUTIONS: &str = include_str!("demo/solutions.json");
+
+// How long to wait on a single request by default, in seconds.
+
+const TIMEOUT_SECS: u64 = 30;
+
+// Bad arguments exit with 2, the same as clap's own usage errors, and
+// anything else going wrong (a failed fetch, an unreadable file) with 1.
+// --exit-code margin's codes start at 10 to stay clear of both.
+
+const USAGE_ERROR: u8 = 2;
+
+// Comparing golfers is what the program is for, so it's what happens
+// without a subcommand, too.
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct HoleLink {
-    name: String,
-    url: String,
+    #[command(flatten)]
+    compare: Option<Arguments>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Solution {
-    bytes: usize,
-    chars: usize,
-    #[serde(alias = "login")] golfer: String,
-    hole: String,
-    lang: String,
-    scoring: String,
-    submitted: String,
-
-    #[serde(default)] length: usize,    // Copy of bytes or chars.
-    #[serde(default)] rank: usize,      // Computed by us.
-    #[serde(default)] score: f32,       // Computed by us.
+#[derive(Subcommand)]
+enum Command {
+    /// Compare <me> against <them> (what happens without a subcommand).
+    Compare(Box<Arguments>),
+
+    /// List every hole's ID, name, and category.
+    Holes(ApiArguments),
+
+    /// List every language's ID and name.
+    Langs(ApiArguments),
 }
 
-struct SolutionLog {
-    hole_id: String,
-    solutions: Vec<Solution>,
-    gold_length: usize,
-    golfers: Vec<String>,
-    scoring: String,
-    hole_name_width: usize,
-    bar_width: usize,
+#[derive(Args)]
+struct ApiArguments {
+    /// Where to find the code.golf API, for going through a mirror or a
+    /// proxy, or testing against a local server.
+    #[arg(long, env = "HOLIER_API_BASE", default_value = API_BASE, value_parser = parse_api_base)]
+    api_base: String,
 }
 
-#[derive(Parser)]
+#[derive(Args, Clone)]
 struct Arguments {
     me: String,
-    them: String,
-    #[arg(short, long, default_value="rust" )] lang: String,
-    #[arg(short, long, default_value="bytes")] scoring: String,
-    #[arg(short, long                       )] as_of: Option<String>,
-    #[arg(       long                       )] reference: Option<String>,
-    #[arg(       long, default_value="33"   )] hole_name_width: usize,
-    #[arg(       long, default_value="20"   )] score_bar_width: usize,
-    #[arg(short, long                       )] reverse: bool,
+    #[arg(required_unless_present_any = ["compare_to_self_past", "opponents_file", "rival", "them_file"])] them: Vec<String>,
+    #[arg(short, long, default_value="rust"                            )] lang: String,
+    #[arg(short, long, default_value="bytes", value_parser=SCORINGS    )] scoring: String,
+    #[arg(short, long                                                  )] as_of: Option<String>,
+    #[arg(       long                                                  )] reference: Option<String>,
+    #[arg(       long                                                  )] hole_name_width: Option<usize>,
+    #[arg(       long                                                  )] score_bar_width: Option<usize>,
+    #[arg(short, long                                                  )] reverse: bool,
+
+    /// Only compare these holes (by ID, like "fizz-buzz", or any part of
+    /// one that only matches one hole). Can be given more than once, or
+    /// as a comma-separated list.
+    #[arg(long, value_delimiter = ',')]
+    hole: Vec<String>,
+
+    /// Only compare the holes in this category (like "Mathematics"). Can
+    /// be given more than once.
+    #[arg(long)]
+    category: Vec<String>,
+
+    /// Group the holes by category, with each category's own wins,
+    /// draws, and losses under it.
+    #[arg(long)]
+    group_by_category: bool,
+
+    /// List each hole's reference links (Wikipedia and the like) under
+    /// it in the report.
+    #[arg(long)]
+    links: bool,
+
+    /// Refer to holes by their IDs (like "fizz-buzz") instead of their
+    /// names (like "Fizz Buzz").
+    #[arg(long)]
+    show_id: bool,
+
+    /// Show hole names in full even when they don't fit in the name
+    /// column, instead of cutting them short with an ellipsis.
+    #[arg(long)]
+    no_truncate: bool,
+
+    /// Show the medal (if any) each golfer holds on each hole, with a
+    /// diamond for an uncontested gold.
+    #[arg(long)]
+    medals: bool,
+
+    /// Show where each golfer places on each hole's whole leaderboard,
+    /// like "#7 vs. #3 of 42".
+    #[arg(long)]
+    field_rank: bool,
+
+    /// Show when each golfer last improved their solution to each hole
+    /// (as of the cutoff, if there is one).
+    #[arg(long)]
+    show_dates: bool,
+
+    /// Include holes that only one of the two golfers has solved. By
+    /// default, a hole is only listed if both golfers have a solution
+    /// for it. A hole solved by only one golfer counts as a win for them.
+    #[arg(long, visible_alias = "no-both-required")]
+    all_holes: bool,
+
+    /// Leave out the holes where both golfers are tied at the gold length,
+    /// both from the list and from the totals.
+    #[arg(long)]
+    exclude_gold_draws: bool,
+
+    /// Only list the holes I'm winning. The totals still count every hole.
+    #[arg(long, conflicts_with_all = ["only_losses", "only_ties"])]
+    only_wins: bool,
+
+    /// Only list the holes I'm losing. The totals still count every hole.
+    #[arg(long, conflicts_with_all = ["only_wins", "only_ties"])]
+    only_losses: bool,
+
+    /// Only list the holes we're tied on. The totals still count every
+    /// hole.
+    #[arg(long, conflicts_with_all = ["only_wins", "only_losses"])]
+    only_ties: bool,
+
+    /// Only list the holes where we're at least this many bytes (or chars)
+    /// apart. The totals still count every hole.
+    #[arg(long, default_value_t = 0, value_name = "N")]
+    min_delta: usize,
+
+    /// Only list the first this-many holes (after sorting). The totals
+    /// still count every hole.
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Re-fetch a hole's solution log a couple of times if it comes back
+    /// empty, in case the API is returning empty logs transiently.
+    #[arg(long)]
+    retry_on_empty: bool,
+
+    /// How long a cached solution log stays fresh (like "1h", "2d", or
+    /// "1w").
+    #[arg(long, value_parser = parse_duration, default_value = "1h")]
+    cache_ttl: TimeDelta,
+
+    /// Always fetch solution logs from the API, rather than reusing ones
+    /// fetched recently.
+    #[arg(long)]
+    no_cache: bool,
+
+    /// How long a cached holes list stays fresh (like "12h" or "1w").
+    #[arg(long, value_parser = parse_duration, default_value = "1d")]
+    holes_ttl: TimeDelta,
+
+    /// Fetch the holes list from the API even if a fresh one is cached
+    /// (say, when a new hole has just come out).
+    #[arg(long)]
+    refresh_holes: bool,
+
+    /// List the holes that pass the filters and the requests that would be
+    /// made for them, without making any (besides fetching the holes list).
+    #[arg(long)]
+    dry_run: bool,
+
+    /// How many times to retry a request that fails before giving up.
+    #[arg(long, default_value = "9")]
+    max_retries: u32,
+
+    /// How long to wait before the first retry, in milliseconds. Each
+    /// retry after that waits twice as long as the last (up to a cap).
+    #[arg(long, default_value = "100")]
+    retry_base_ms: u64,
+
+    /// Where to find the code.golf API, for going through a mirror or a
+    /// proxy, or testing against a local server.
+    #[arg(long, env = "HOLIER_API_BASE", default_value = API_BASE, value_parser = parse_api_base)]
+    api_base: String,
+
+    /// How long to wait on a single request before giving up on it and
+    /// retrying, in seconds.
+    #[arg(long, default_value_t = TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout_secs: u64,
+
+    /// Keep the report up to date, redoing it every this-many seconds
+    /// (until Ctrl-C). Cached solution logs are only reused for as long as
+    /// one refresh.
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    watch: Option<u64>,
+
+    /// How many solution logs to fetch at once. Lower this if the API is
+    /// rate-limiting you.
+    #[arg(long, default_value = "8", value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency: u32,
+
+    /// Print only the summary totals, as a single JSON object. Same as
+    /// --format totals-json.
+    #[arg(long)]
+    totals_json: bool,
+
+    /// How to draw the golfers' markers on the score bar. `blocks` uses
+    /// partial block characters to show positions to an eighth of a cell.
+    #[arg(long, value_enum, default_value = "letters")]
+    bar_style: BarStyle,
+
+    /// Scale the score bars so that my best-scoring hole in the report
+    /// fills the bar. The bars then show scores relative to my best hole,
+    /// not relative to the gold.
+    #[arg(long)]
+    normalize_scores: bool,
+
+    /// How to turn a score into a position on the score bar. `truncate`
+    /// always rounds down, which biases markers leftward on narrow bars.
+    #[arg(long, value_enum, default_value = "nearest")]
+    bar_rounding: BarRounding,
+
+    /// Compare your current standing against your own standing at the
+    /// --as-of date, instead of against another golfer.
+    #[arg(long, requires = "as_of", conflicts_with = "them")]
+    compare_to_self_past: bool,
+
+    /// Also show each hole's delta as a percentage of the shorter solution.
+    #[arg(long)]
+    delta_percent: bool,
+
+    /// Only count solutions submitted on or after this date.
+    #[arg(long)]
+    submitted_after: Option<String>,
+
+    /// Only count solutions submitted before this date.
+    #[arg(long)]
+    submitted_before: Option<String>,
+
+    /// What kind of report to print. `statusbar` prints just a tiny
+    /// one-line summary, for embedding in tmux/polybar and the like.
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Only compare holes whose name or description matches this
+    /// (case-insensitive) regex.
+    #[arg(long)]
+    grep: Option<String>,
+
+    /// Under each hole, also show the top three on its leaderboard (and
+    /// where I am, if I'm not one of them).
+    #[arg(long)]
+    mini_board: bool,
+
+    /// How the summary line says who's ahead. `plain` and `verbose` drop
+    /// the exclamation marks, for reports shared outside of the terminal.
+    #[arg(long, value_enum, default_value = "playful")]
+    summary_format: SummaryFormat,
+
+    /// How to order the holes: by how I'm doing against <them> (the
+    /// default), by my score or theirs (highest first), by hole name or
+    /// category (A to Z), or by how far I am from the gold (closest first).
+    /// --reverse flips whichever order this picks.
+    #[arg(long, value_enum, default_value = "delta")]
+    sort: SortMode,
+
+    /// Set the exit code based on the summary. With `margin`: 0 if I'm
+    /// winning by more than --narrow-lead, 10 if I'm winning by less than
+    /// that, 11 if we're tied, and 12 if I'm behind. (1 and 2 still mean
+    /// something went wrong, or the arguments were bad.)
+    #[arg(long, value_enum)]
+    exit_code: Option<ExitCodeMode>,
+
+    /// The biggest lead (in holes) that --exit-code margin calls narrow.
+    #[arg(long, default_value = "3")]
+    narrow_lead: usize,
+
+    /// Also show how many holes are within this many bytes/chars of
+    /// flipping, split into those I'm just ahead on and just behind on.
+    /// Defaults to 1 if given without a value.
+    #[arg(long, num_args = 0..=1, default_missing_value = "1")]
+    near: Option<usize>,
+
+    /// Also show the average and median delta over the holes we've both
+    /// solved, and how many holes neither of us has.
+    #[arg(long)]
+    stats: bool,
+
+    /// Extra `key=value` query parameter to pass along when fetching
+    /// current scores (can be given more than once). Has no effect on
+    /// historical reports.
+    #[arg(long, value_parser = parse_query_pair)]
+    scores_query: Vec<(String, String)>,
+
+    /// Only compare holes where one of the two golfers' best solution was
+    /// submitted within this long of now, e.g. `30d`, `2w`, or `12h`.
+    #[arg(long, value_parser = parse_duration)]
+    active_within: Option<TimeDelta>,
+
+    /// Only accept full timestamps (with a time of day) for --as-of and
+    /// --submitted-after/--submitted-before, and print the exact boundaries
+    /// being used.
+    #[arg(long)]
+    strict_dates: bool,
+
+    /// Instead of the usual report, show both golfers' full submission
+    /// history for this hole side by side.
+    #[arg(long, conflicts_with_all = ["compare_to_self_past", "flip_lang"])]
+    history: Option<String>,
+
+    /// Instead of the usual report, show step by step how the ranks and
+    /// scores for this hole were worked out.
+    #[arg(long, conflicts_with_all = ["compare_to_self_past", "flip_lang", "history"])]
+    explain: Option<String>,
+
+    /// Instead of the usual report, show how everyone's best solution to
+    /// this hole got shorter over time, one improvement per line.
+    #[arg(long, conflicts_with_all = ["compare_to_self_past", "flip_lang", "history", "explain", "since", "flipped_since", "opportunities", "opponents_file", "rival"])]
+    timeline: Option<String>,
+
+    /// Instead of the usual report, list the holes where who's winning
+    /// differs between --lang and this language.
+    #[arg(long, conflicts_with_all = ["demo", "offline", "compare_to_self_past"])]
+    flip_lang: Option<String>,
+
+    /// Instead of the usual report, list the holes where something changed
+    /// between this date and --until (or now): how much each of us took
+    /// off, and whether the lead changed hands.
+    #[arg(long, conflicts_with_all = ["as_of", "compare_to_self_past", "flip_lang", "history", "explain", "opponents_file"])]
+    since: Option<String>,
+
+    /// The later of the two dates for --since.
+    #[arg(long, requires = "since")]
+    until: Option<String>,
+
+    /// Mark the holes where who's ahead is different now from how it was
+    /// as of this date.
+    #[arg(long, conflicts_with_all = ["since", "compare_to_self_past", "flip_lang", "history", "explain", "opponents_file"])]
+    flipped_since: Option<String>,
+
+    /// Instead of the usual report, list the holes <them> has solved and
+    /// I haven't, shortest solution first, as a to-do list.
+    #[arg(long, conflicts_with_all = ["compare_to_self_past", "flip_lang", "history", "explain"])]
+    opportunities: bool,
+
+    /// Instead of naming <them>, compare against whoever is just above me
+    /// in the overall standings for --lang (over the holes being compared).
+    #[arg(long, conflicts_with_all = ["them", "compare_to_self_past", "opponents_file", "history", "explain"])]
+    rival: bool,
+
+    /// Instead of comparing against one golfer, compare against each golfer
+    /// listed (one per line) in this file and print a league table.
+    #[arg(long, conflicts_with_all = ["them", "compare_to_self_past", "flip_lang", "history", "explain"])]
+    opponents_file: Option<String>,
+
+    /// Instead of comparing against one golfer, compare against each golfer
+    /// listed (one per line) in this file, with a full report for each.
+    /// Passing "-" for <them> reads the list from stdin instead.
+    #[arg(long, conflicts_with_all = ["them", "compare_to_self_past", "opponents_file", "rival", "watch", "output", "output_dir"])]
+    them_file: Option<String>,
+
+    /// Write the report to this file instead of printing it. Colors are
+    /// left out unless --force-color is given.
+    #[arg(short, long, conflicts_with = "output_dir")]
+    output: Option<String>,
+
+    /// Write the report to files in this directory (one per --formats
+    /// entry, named after the cutoff) instead of printing it.
+    #[arg(long)]
+    output_dir: Option<String>,
+
+    /// Comma-separated list of formats to write with --output-dir.
+    /// Defaults to just --format.
+    #[arg(long, value_enum, value_delimiter = ',', requires = "output_dir")]
+    formats: Vec<Format>,
+
+    /// Use a small bundled sample dataset instead of the code.golf API.
+    /// The sample golfers are alice, bob, carol, dave, and erin.
+    #[arg(long)]
+    demo: bool,
+
+    /// Read the holes list and solution logs from this directory (as
+    /// holes.json and <hole>.json, like --dump writes) instead of the
+    /// code.golf API.
+    #[arg(long, conflicts_with = "demo")]
+    offline: Option<std::path::PathBuf>,
+
+    /// Save the holes list and each solution log, just as the API sent
+    /// them, to this directory (in the layout --offline reads).
+    #[arg(long, conflicts_with_all = ["demo", "offline", "flip_lang"])]
+    dump: Option<std::path::PathBuf>,
+
+    /// Use colors even when the report isn't going to a terminal (say,
+    /// when it's piped somewhere, or written with --output).
+    #[arg(long, conflicts_with = "no_color")]
+    force_color: bool,
+
+    /// Log what's going on behind the scenes to stderr: each solution log
+    /// fetch, how it went, and how long it took. Pass it twice to also log
+    /// retries, or three times to log every request.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Don't print progress messages or warnings, just the report.
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Print without colors. Colors are also left out when the NO_COLOR
+    /// environment variable is set, or when stdout isn't a terminal.
+    #[arg(long)]
+    no_color: bool,
+
+    /// Which colors to draw the report with. "colorblind" uses blue and
+    /// orange instead of green and red, and "mono" leaves out every hue
+    /// (keeping the greys, bold, and underlining).
+    #[arg(long, value_enum, default_value = "normal")]
+    palette: PaletteName,
+
+    /// Which characters to draw the report with. "ascii" swaps every
+    /// non-ASCII glyph (bars, bullets, arrows, and so on) for a plain
+    /// ASCII one, for terminals and logs that can't show them.
+    #[arg(long, value_enum, default_value = "utf8")]
+    encode: Encode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PaletteName {
+    Normal,
+    Colorblind,
+    Mono,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Encode {
+    Utf8,
+    Ascii,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortMode {
+    Delta,
+    MyScore,
+    TheirScore,
+    Hole,
+    Category,
+    GoldMargin,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExitCodeMode {
+    Margin,
+}
+
+// Parse a span of time like "12h", "30d", "2w", or "3mo" (a month being
+// 30 days, for these purposes). Spans get counted back from now, so one
+// too long to do that with is turned away here.
+
+fn parse_duration(text: &str) -> Result<TimeDelta, String> {
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (count, unit) = text.split_at(split);
+    let count: i64 = count.parse().map_err(|_| format!("expected something like 30d, got '{text}'"))?;
+
+    let span = match unit {
+        "h"  => TimeDelta::try_hours(count),
+        "d"  => TimeDelta::try_days(count),
+        "w"  => TimeDelta::try_weeks(count),
+        "mo" => count.checked_mul(30).and_then(TimeDelta::try_days),
+        _    => return Err(format!("unknown unit '{unit}' (expected h, d, w, or mo)")),
+    };
+
+    span.filter(|&span| Utc::now().checked_sub_signed(span).is_some())
+        .ok_or_else(|| format!("'{text}' is too long a span of time"))
+}
+
+fn parse_api_base(url: &str) -> Result<String, String> {
+    match url.starts_with("https://") || url.starts_with("http://") {
+        true  => Ok(url.trim_end_matches('/').to_string()),
+        false => Err(format!("expected a URL starting with https:// or http://, got '{url}'")),
+    }
+}
+
+fn parse_query_pair(pair: &str) -> Result<(String, String), String> {
+    match pair.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value, got '{pair}'")),
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<ExitCode, Box<dyn Error>> {
     
     // Parse arguments.
 
-    let args = Arguments::parse();
-    let mut golfers = vec![args.me, args.them];
+    let cli = Cli::parse();
+
+    let mut args = match cli.command {
+        Some(Command::Compare(args)) => *args,
+        Some(Command::Holes(api)) => return list_holes(&build_client(TIMEOUT_SECS)?, &api.api_base).await,
+        Some(Command::Langs(api)) => return list_langs(&build_client(TIMEOUT_SECS)?, &api.api_base).await,
+        None => cli.compare.unwrap(),
+    };
+
+    // With --verbose, log to stderr (alongside the progress messages), with
+    // how long each span took once it's done.
 
-    if let Some(reference) = args.reference {
-        golfers.push(reference);
+    if args.verbose > 0 {
+        let level = match args.verbose {
+            1 => tracing::Level::INFO,
+            2 => tracing::Level::DEBUG,
+            _ => tracing::Level::TRACE,
+        };
+
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_writer(std::io::stderr)
+            .with_ansi(!args.no_color && std::io::stderr().is_terminal())
+            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+            .init();
+    }
+
+    if args.encode == Encode::Ascii {
+        GLYPHS.set(&ASCII_GLYPHS).ok();
     }
 
-    let cutoff_provided = args.as_of.is_some();
-    let mut cutoff = args.as_of.unwrap_or(Utc::now().format("%Y-%m-%d").to_string());
+    // Leave colors out when asked to (via NO_COLOR or --no-color) or when
+    // the report isn't going to a terminal (including --output and
+    // --output-dir files), unless --force-color says otherwise. They're
+    // always left out if the terminal can't show them.
 
-    // Validate the date just a little to make it not be a massive
-    // UI issue.
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let to_file = args.output.is_some() || args.output_dir.is_some();
+
+    let colorless = args.no_color || no_color || to_file || !std::io::stdout().is_terminal();
+
+    if (colorless && !args.force_color) || !enable_colors() {
+        PALETTE.set(&PLAIN_PALETTE).ok();
+    }
+
+    match args.palette {
+        PaletteName::Normal     => {},
+        PaletteName::Colorblind => {PALETTE.set(&COLORBLIND_PALETTE).ok();},
+        PaletteName::Mono       => {PALETTE.set(&MONO_PALETTE).ok();},
+    }
+
+    // One client for every request, so that connections get reused across
+    // the hundred-odd solution log fetches (and, with --watch or
+    // --them-file, across reports).
+
+    let client = build_client(args.timeout_secs)?;
+
+    // With --them-file (or "-" for <them>, meaning stdin), each golfer
+    // listed gets a report of their own, one after the other. The logs
+    // fetched for the first report come from the cache for the rest.
+
+    let stdin = args.them == ["-"];
+
+    if stdin && (args.watch.is_some() || args.output.is_some() || args.output_dir.is_some()) {
+        eprintln!("Reading <them> from stdin can't be combined with --watch, --output, or --output-dir.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    let them_file = if stdin {Some("-".to_string())} else {args.them_file.clone()};
+
+    if let Some(path) = them_file {
+        let text = if path == "-" {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            std::fs::read_to_string(&path)
+        };
+
+        let rivals: Vec<String> = match text {
+            Ok(text) => text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect(),
+            Err(err) => {
+                eprintln!("Couldn't read the list of golfers from \"{path}\": {err}");
+                return Ok(ExitCode::FAILURE);
+            }
+        };
+
+        if rivals.is_empty() {
+            eprintln!("The list of golfers to compare against is empty.");
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
+
+        let Palette {bold, reset, ..} = palette();
+        let mut exit_code = ExitCode::SUCCESS;
+
+        for rival in rivals {
+            println!("{bold}{} v. {}{reset}\n", sanitize(&args.me), sanitize(&rival));
+
+            let code = run(Arguments {them: vec![rival], ..args.clone()}, &client).await?;
+
+            if code != ExitCode::SUCCESS {
+                exit_code = code;
+            }
+        }
+
+        return Ok(exit_code);
+    }
+
+    let Some(interval) = args.watch else {
+        return run(args, &client).await;
+    };
+
+    // With --watch, redo the whole report every interval, clearing the
+    // screen in between if the report's going to a terminal (otherwise
+    // each one just gets printed after the last). A cached log older than
+    // one refresh is out of date as far as watching is concerned.
+
+    args.cache_ttl = args.cache_ttl.min(TimeDelta::seconds(interval as i64));
+
+    let clear = !args.no_color && args.output.is_none() && std::io::stdout().is_terminal();
+
+    loop {
+        if clear {
+            print!("\x1b[2J\x1b[H");
+        }
+
+        tokio::select! {
+            code = run(args.clone(), &client) => {code?;},
+            _ = tokio::signal::ctrl_c() => return Ok(ExitCode::SUCCESS),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(interval)) => {},
+            _ = tokio::signal::ctrl_c() => return Ok(ExitCode::SUCCESS),
+        }
+    }
+}
+
+// The holes subcommand: every hole, one per line, in the API's order.
+
+async fn list_holes(client: &reqwest::Client, api_base: &str) -> Result<ExitCode, Box<dyn Error>> {
+    let holes = get_holes(client, api_base).await?;
+
+    let id_width   = holes.iter().map(|hole| hole.id.width()).max().unwrap_or(0);
+    let name_width = holes.iter().map(|hole| sanitize(&hole.name).width()).max().unwrap_or(0);
+
+    for hole in &holes {
+        let name = sanitize(&hole.name);
+
+        println!(
+            "{}{:id_padding$}  {name}{:name_padding$}  {}",
+            hole.id, "", "", sanitize(&hole.category),
+            id_padding   = id_width - hole.id.width(),
+            name_padding = name_width - name.width(),
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+// The langs subcommand: every language, one per line.
+
+async fn list_langs(client: &reqwest::Client, api_base: &str) -> Result<ExitCode, Box<dyn Error>> {
+    let langs = get_langs(client, api_base).await?;
+    let id_width = langs.iter().map(|lang| lang.id.width()).max().unwrap_or(0);
+
+    for lang in &langs {
+        println!("{}{:padding$}  {}", lang.id, "", sanitize(&lang.name), padding = id_width - lang.id.width());
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
-    #[derive(PartialEq, Eq)]
-    enum CutoffType {IncludeEnd, ExcludeEnd}
-    use CutoffType::*;
-
-    let date_regexes = vec![
-        (IncludeEnd, Regex::new(r"^\d\d\d\d$").unwrap()),
-        (IncludeEnd, Regex::new(r"^\d\d\d\d-\d\d$").unwrap()),
-        (IncludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d$").unwrap()),
-        (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d$").unwrap()),
-        (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d:\d\d$").unwrap()),
-        (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d:\d\d.\d+$").unwrap()),
-    ];
-
-    let date_format =
-        date_regexes
-            .iter()
-            .find(|(_cutoff_type, regex)| regex.is_match(&cutoff));
-
-    match date_format {
-        Some((cutoff_type, _)) => {
-            if *cutoff_type == IncludeEnd {
-                cutoff += "z";
+// Fetch, process, and render one report.
+
+async fn run(mut args: Arguments, client: &reqwest::Client) -> Result<ExitCode, Box<dyn Error>> {
+
+    // A relative date like "7d" or "2w ago" means that long before now.
+
+    for date in [&mut args.as_of, &mut args.since, &mut args.until, &mut args.flipped_since, &mut args.submitted_after, &mut args.submitted_before].into_iter().flatten() {
+        if let Ok(span) = parse_duration(date.strip_suffix(" ago").unwrap_or(date))
+        && let Some(at) = Utc::now().checked_sub_signed(span) {
+            *date = at.format("%Y-%m-%d %H:%M:%S").to_string();
+        }
+    }
+
+    // --scoring both is a bytes report as far as everything but the text
+    // report is concerned (the chars side gets worked out separately).
+
+    let both_scorings = args.scoring == "both";
+
+    if both_scorings {
+        args.scoring = "bytes".to_string();
+    }
+
+    // Same with --scoring points: holes are still won and lost on bytes,
+    // and the points are the same length-ratio score the bars are drawn
+    // from (1000 for the gold, scaled down by how much longer a solution
+    // is), rounded. That's not a recreation of the site's own rankings.
+
+    let points = args.scoring == "points";
+
+    if points {
+        args.scoring = "bytes".to_string();
+    }
+
+    let Palette {yellow, reset, ..} = palette();
+
+    // Without --hole-name-width or --score-bar-width, size the columns to
+    // fill the terminal, if there is one.
+
+    let (hole_name_width, score_bar_width) = column_widths(args.hole_name_width, args.score_bar_width);
+
+    // With --opponents-file, everyone in the file is a <them>.
+
+    let opponents: Vec<String> = match &args.opponents_file {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(text) => text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect(),
+            Err(err) => {
+                eprintln!("Couldn't read opponents file \"{path}\": {err}");
+                return Ok(ExitCode::FAILURE);
             }
         },
-        None => {
-            println!("Invalid date format. Try a date in one of these formats:");
-            println!("    — 2025");
-            println!("    — 2025-03");
-            println!("    — 2025-03-31");
-            println!("    — 2025-03-31 12:15");
-            println!("    — 2025-03-31 12:15:29");
-            println!("    — 2025-03-31 12:15:29.185779");
-            return Ok(());
+        None => vec![],
+    };
+
+    if args.opponents_file.is_some() && opponents.is_empty() {
+        eprintln!("The opponents file doesn't list any golfers.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // With --compare-to-self-past, <them> is <me> as of the cutoff. Their
+    // solutions get relabeled under this name once they've been processed.
+
+    let them = if !args.them.is_empty() {
+        args.them.clone()
+    } else if args.rival {
+        vec![String::new()]     // Worked out once the logs are in.
+    } else if args.compare_to_self_past {
+        vec![format!("{}@{}", args.me, args.as_of.clone().unwrap())]
+    } else {
+        opponents.clone()
+    };
+
+    // Passing more than one <them> ranks everyone on each hole instead of
+    // going head to head.
+
+    let ranking = args.them.len() > 1;
+
+    let mut golfers = [vec![args.me], them].concat();
+    let ranked = golfers.clone();
+
+    if let Some(reference) = &args.reference {
+        golfers.push(reference.clone());
+    }
+
+    if let Some((first, second)) = repeated_golfer(&golfers) {
+        eprintln!("\"{first}\" and \"{second}\" are the same golfer (logins aren't case-sensitive), so there'd be nothing to compare.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // Progress messages (and warnings) go to stderr, so that stdout is only
+    // ever the report itself. With --quiet, they don't go anywhere.
+
+    let format = if args.totals_json {Format::TotalsJson} else {args.format};
+    let machine_output = format != Format::Text || args.output_dir.is_some();
+
+    // The views other than the head-to-head comparison only come as text,
+    // so asking for them in another format is a mistake, rather than
+    // something to quietly ignore.
+
+    let text_only = [
+        (args.history.is_some(),        "--history"),
+        (args.explain.is_some(),        "--explain"),
+        (args.timeline.is_some(),       "--timeline"),
+        (args.since.is_some(),          "--since"),
+        (args.flip_lang.is_some(),      "--flip-lang"),
+        (args.opponents_file.is_some(), "--opponents-file"),
+        (args.opportunities,            "--opportunities"),
+        (ranking,                       "Ranking more than two golfers"),
+    ].into_iter().find_map(|(given, view)| given.then_some(view));
+
+    if let Some(view) = text_only && machine_output {
+        eprintln!("{view} only makes a text report, so it can't be combined with --format, --totals-json, or --output-dir.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    let status = |line: &str| if !args.quiet {
+        eprintln!("{line}");
+    };
+
+    // With --since, the cutoff is the later of the two dates.
+
+    let cutoff_provided = args.as_of.is_some() || args.until.is_some();
+    let mut cutoff = args.as_of.clone().or(args.until.clone()).unwrap_or(Utc::now().format("%Y-%m-%d").to_string());
+
+    // Validate the dates just a little to make them not be a massive
+    // UI issue.
+
+    let window = [&args.submitted_after, &args.submitted_before];
+
+    let dates = [&args.since, &args.flipped_since, &args.submitted_after, &args.submitted_before];
+
+    if date_bounds(&cutoff).is_none() || dates.iter().any(|date| date.as_ref().is_some_and(|date| date_bounds(date).is_none())) {
+        eprintln!("Invalid date format. Try a date in one of these formats:");
+        eprintln!("    {} 2025", glyphs().bullet);
+        eprintln!("    {} 2025-03", glyphs().bullet);
+        eprintln!("    {} 2025-03-31", glyphs().bullet);
+        eprintln!("    {} 2025-03-31 12:15", glyphs().bullet);
+        eprintln!("    {} 2025-03-31 12:15:29", glyphs().bullet);
+        eprintln!("    {} 2025-03-31 12:15:29.185779", glyphs().bullet);
+        eprintln!("    {} 7d, 24h, 2w, or 1mo (optionally followed by \"ago\"), meaning that long ago", glyphs().bullet);
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // With --strict-dates, only full timestamps are accepted, so that a
+    // historical report can't quietly take in a whole extra day (or month,
+    // or year) of solutions. The exact boundaries get spelled out, too.
+
+    if args.strict_dates {
+        let provided = [
+            ("before",       &args.as_of),
+            ("before",       &args.since),
+            ("before",       &args.until),
+            ("before",       &args.flipped_since),
+            ("on or after",  &args.submitted_after),
+            ("before",       &args.submitted_before),
+        ];
+
+        if provided.iter().any(|(_, date)| date.as_ref().is_some_and(|date| date_format(date) != Some(CutoffType::ExcludeEnd))) {
+            eprintln!("--strict-dates requires a full timestamp. Try a date in one of these formats:");
+            eprintln!("    {} 2025-03-31 12:15", glyphs().bullet);
+            eprintln!("    {} 2025-03-31 12:15:29", glyphs().bullet);
+            eprintln!("    {} 2025-03-31 12:15:29.185779", glyphs().bullet);
+            eprintln!("    {} 7d, 24h, 2w, or 1mo (optionally followed by \"ago\"), meaning that long ago", glyphs().bullet);
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
+
+        for (relation, date) in provided {
+            if let Some(date) = date {
+                let seconds = if date.len() == "2025-03-31 12:15".len() {":00"} else {""};
+                status(&format!("Counting solutions submitted {relation} {date}{seconds} UTC."));
+            }
         }
     }
 
-    // Get a list of all hole IDs via the API.
+    // Solutions count toward the report if they were submitted before the
+    // end of the cutoff, and (with --submitted-after/--submitted-before)
+    // between the starts of the window's ends.
 
-    println!();
-    println!("Fetching list of holes...");
+    let (_, cutoff_at) = date_bounds(&cutoff).unwrap();
+    let since_at  = args.since.as_ref().or(args.flipped_since.as_ref()).map(|date| date_bounds(date).unwrap().1);
+    let after_at  = args.submitted_after .as_ref().map(|date| date_bounds(date).unwrap().0);
+    let before_at = args.submitted_before.as_ref().map(|date| date_bounds(date).unwrap().0);
 
-    let holes_resp = reqwest::get("http://code.golf/api/holes").await?.text().await?;
-    let holes: Vec<Hole> = serde_json::from_str(&holes_resp).unwrap();
+    // Comparing then with now only makes sense if then comes first.
 
-    // Collect the full solutions log for each hole in the selected language.
+    if since_at.is_some_and(|since_at| since_at >= cutoff_at) {
+        let flag = if args.since.is_some() {"--since"} else {"--flipped-since"};
+        eprintln!("{flag} has to be earlier than the cutoff ({cutoff}).");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
 
-    println!("Fetching solution log for each hole (this will take several seconds)...");
+    // Windowing which solutions count needs the full solutions log, the same
+    // as a historical report does.
+
+    let windowed = window.iter().any(|date| date.is_some());
+    let historical = cutoff_provided || windowed || since_at.is_some() || args.history.is_some() || args.explain.is_some() || args.timeline.is_some();
+
+    // Get a list of all hole IDs via the API (or, with --demo, from the
+    // bundled sample data, or with --offline, from a file).
+
+    status("");
+
+    let local = args.demo || args.offline.is_some();
+
+    let holes_resp = if args.demo {
+        status("Using the bundled demo data instead of the code.golf API.");
+        DEMO_HOLES.to_string()
+    } else if let Some(dir) = &args.offline {
+        status(&format!("Reading holes and solution logs from {} instead of the code.golf API.", dir.display()));
+
+        match std::fs::read_to_string(dir.join("holes.json")) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("Couldn't read the holes list from {}: {err}", dir.join("holes.json").display());
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    } else {
+        status("Fetching list of holes...");
+        get_holes_text(client, &args.api_base, (!args.refresh_holes).then_some(args.holes_ttl)).await?
+    };
+
+    // With --dump, the holes list gets saved alongside the solution logs.
+    // Each dumped log is for one language, so --lang all can't be dumped.
+
+    if let Some(dir) = &args.dump {
+        if args.lang == "all" {
+            eprintln!("--dump saves one language's solution logs, so it can't be used with --lang all.");
+            return Ok(ExitCode::FAILURE);
+        }
+
+        if let Err(err) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(dir.join("holes.json"), &holes_resp)) {
+            eprintln!("Couldn't save the holes list to {}: {err}", dir.display());
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    let mut holes: Vec<Hole> = match serde_json::from_str(&holes_resp) {
+        Ok(holes) => holes,
+        Err(err) => {
+            eprintln!("Couldn't make sense of the holes list: {err}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    // Guard against the catalog listing the same hole more than once.
+
+    let duplicates = dedup_holes(&mut holes);
+
+    if duplicates > 0 {
+        status(&format!("{yellow}Warning:{reset} the holes list contained {duplicates} duplicate entr{}; ignoring them", if duplicates == 1 {"y"} else {"ies"}));
+    }
+
+    // The categories --category can pick from, out of the whole catalog
+    // (before the other filters narrow it down).
+
+    let mut categories: Vec<String> = holes.iter().map(|hole| hole.category.clone()).collect();
+    categories.sort();
+    categories.dedup();
+
+    // With --history, --explain, or --timeline, the only hole we need is the
+    // one being looked at.
+
+    if let Some(hole_id) = args.history.as_ref().or(args.explain.as_ref()).or(args.timeline.as_ref()) {
+        holes.retain(|hole| hole.id == *hole_id);
+
+        if holes.is_empty() {
+            eprintln!("There's no hole with the ID \"{hole_id}\".");
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
+    }
+
+    // Which of --hole, --category, and --grep (if any) left no holes at all.
+
+    let mut emptied_by = None;
+
+    // Narrow down to the --hole holes (if any were given). An ID that isn't
+    // a hole's exact ID can still pick out a hole by being part of just the
+    // one hole's ID.
+
+    if !args.hole.is_empty() {
+        let mut wanted = HashSet::new();
+
+        for id in &args.hole {
+            let matches: Vec<&Hole> = match holes.iter().find(|hole| hole.id == *id) {
+                Some(hole) => vec![hole],
+                None => holes.iter().filter(|hole| hole.id.contains(id.as_str())).collect(),
+            };
+
+            match matches[..] {
+                [hole] => {wanted.insert(hole.id.clone());},
+                [] => {
+                    eprintln!("There's no hole with the ID \"{id}\".");
+                    return Ok(ExitCode::from(USAGE_ERROR));
+                },
+                _ => {
+                    eprintln!("\"{id}\" could be any of these holes:");
+
+                    for hole in matches {
+                        eprintln!("    {} {}", glyphs().bullet, hole.id);
+                    }
+
+                    return Ok(ExitCode::from(USAGE_ERROR));
+                },
+            }
+        }
+
+        holes.retain(|hole| wanted.contains(&hole.id));
+        emptied_by = emptied_by.or(holes.is_empty().then_some("--hole"));
+    }
+
+    // Narrow down to the holes in the --category categories (if any were
+    // given) before fetching any logs.
+
+    if !args.category.is_empty() {
+        if let Some(unknown) = args.category.iter().find(|wanted| !categories.iter().any(|category| category.eq_ignore_ascii_case(wanted))) {
+            eprintln!("There's no hole category called \"{unknown}\". The categories are:");
+
+            for category in categories {
+                eprintln!("    {} {category}", glyphs().bullet);
+            }
+
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
 
-    if cutoff_provided {
-        println!("{YELLOW}Warning:{RESET} historical reports generated using the --as-of flag may include deleted and invalidated solutions");
+        holes.retain(|hole| args.category.iter().any(|wanted| hole.category.eq_ignore_ascii_case(wanted)));
+        emptied_by = emptied_by.or(holes.is_empty().then_some("--category"));
     }
 
-    let futures = holes.iter().map(|hole| (async || 
+    // Narrow down to the holes matching --grep before fetching any logs.
+
+    if let Some(pattern) = &args.grep {
+        let regex = match RegexBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(regex) => regex,
+            Err(err) => {
+                eprintln!("Invalid --grep pattern: {err}");
+                return Ok(ExitCode::from(USAGE_ERROR));
+            }
+        };
+
+        holes.retain(|hole| regex.is_match(&hole.name) || regex.is_match(&hole.preamble));
+        emptied_by = emptied_by.or(holes.is_empty().then_some("--grep"));
+        status(&format!("{} hole{} matched \"{pattern}\".", holes.len(), if holes.len() == 1 {""} else {"s"}));
+    }
+
+    // --history, --explain, and --timeline look at one hole, so there's
+    // nothing to show if the other filters have left it out.
+
+    let single = [("--history", &args.history), ("--explain", &args.explain), ("--timeline", &args.timeline)]
+        .into_iter().find_map(|(flag, hole_id)| hole_id.as_ref().map(|hole_id| (flag, hole_id)));
+
+    if let Some((flag, hole_id)) = single && holes.is_empty() {
+        eprintln!("{flag} {hole_id} was given, but --hole, --category, or --grep leaves that hole out.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // Otherwise, say which filter left nothing to compare, rather than go on
+    // to report that neither of us has solved anything.
+
+    if let Some(flag) = emptied_by {
+        eprintln!("No holes are left once {flag} is applied, so there's nothing to compare.");
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // Check the language(s) against the API's list, since a typo would
+    // otherwise just make for an empty report. A --dry-run skips this
+    // unless it needs the list to know what --lang all would fetch.
+
+    let check_langs = !local && (!args.dry_run || args.lang == "all");
+
+    let known_langs: Vec<Lang> = if !check_langs {
+        vec![]
+    } else {
+        status("Fetching list of languages...");
+
+        get_langs(client, &args.api_base).await?
+    };
+
+    let requested = [Some(&args.lang).filter(|lang| *lang != "all"), args.flip_lang.as_ref()];
+
+    if let Some(unknown) = requested.into_iter().flatten().find(|lang| check_langs && !known_langs.iter().any(|known| &known.id == *lang)) {
+        eprintln!("There's no language with the ID \"{unknown}\". Try one of these:");
+
+        for lang in &known_langs {
+            eprintln!("    {} {} ({})", glyphs().bullet, lang.id, lang.name);
+        }
+
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
+    // With --lang all, we recreate the language-agnostic leaderboard by
+    // pooling every language's log for each hole. The usual per-golfer
+    // dedup below then picks each golfer's best solution across languages.
+
+    let langs: Vec<String> = if args.lang == "all" && !local {
+        known_langs.into_iter().map(|lang| lang.id).collect()
+    } else {
+        vec![args.lang.clone()]
+    };
+
+    let fetch_options = FetchOptions {
+        api_base: args.api_base.clone(),
+        clean_api: !historical,
+        retry_on_empty: args.retry_on_empty,
+        max_retries: args.max_retries,
+        retry_base_ms: args.retry_base_ms,
+        scores_query: args.scores_query.clone(),
+        cache_ttl: if args.no_cache {None} else {Some(args.cache_ttl)},
+        dump: args.dump.clone(),
+    };
+
+    // With --dry-run, say which holes made it through the filters and what
+    // would be requested for each, then stop short of requesting it.
+
+    if args.dry_run {
+        println!("{} hole{} would be reported on:", holes.len(), if holes.len() == 1 {""} else {"s"});
+
+        for hole in &holes {
+            println!("    {} {} ({})", glyphs().bullet, hole.name, hole.id);
+
+            for lang in langs.iter().chain(&args.flip_lang).filter(|_| !local) {
+                println!("        {}", solution_log_url(&fetch_options, lang, &hole.id));
+            }
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Collect the full solutions log for each hole in the selected language(s).
+
+    if local {
+        // Nothing to fetch.
+    } else if langs.len() > 1 {
+        status(&format!("Fetching solution log for each hole in each of {} languages (this will take a while)...", langs.len()));
+    } else {
+        status("Fetching solution log for each hole (this will take several seconds)...");
+    }
+
+    if historical {
+        status(&format!("{yellow}Warning:{reset} historical reports generated using the --as-of or --submitted-after/--submitted-before flags may include deleted and invalidated solutions"));
+    }
+
+    let mut solution_logs: Vec<SolutionLog> = holes.iter().map(|hole|
         SolutionLog {
             hole_id: hole.id.clone(), 
-            solutions: get_solution_log(!cutoff_provided, &args.lang, &hole.id).await,
+            hole_name: hole.name.clone(),
+            category: hole.category.clone(),
+            url: if args.lang == "all" {
+                format!("https://code.golf/{}", hole.id)
+            } else {
+                format!("https://code.golf/{}#{}", hole.id, args.lang)
+            },
+            links: hole.links.clone(),
+            solutions: vec![],
+            leaderboard: vec![],
             gold_length: usize::MAX,
             golfers: golfers.to_vec(),
             scoring: args.scoring.clone(),
             bar_width: 0, // set later
-            hole_name_width: 0, // set later
+            hole_name_width,
+            bar_style: args.bar_style,
+            bar_rounding: args.bar_rounding,
+            bar_scale: 1.0,
+            delta_percent: args.delta_percent,
+            show_id: args.show_id,
+            no_truncate: args.no_truncate,
+            medals: args.medals,
+            field_rank: args.field_rank,
+            show_dates: args.show_dates,
+            lead_flipped: false,
+            reference: args.reference.clone(),
+            points,
+            paired: None,
         }
-    )());
+    ).collect();
+
+    if args.demo {
+        let mut demo_logs: HashMap<String, Vec<Solution>> = serde_json::from_str(DEMO_SOLUTIONS).unwrap();
 
-    let mut solution_logs = futures_util::future::join_all(futures).await;
+        for log in &mut solution_logs {
+            let mut solutions = demo_logs.remove(&log.hole_id).unwrap_or_default();
+            fix_up_dates(&mut solutions);
+            log.solutions = solutions;
+        }
+    }
+
+    if let Some(dir) = &args.offline {
+        for log in &mut solution_logs {
+            match read_offline_log(dir, &log.hole_id) {
+                Ok(solutions) => log.solutions = solutions,
+                Err(err) => {
+                    eprintln!("Couldn't read the solution log for hole \"{}\" from {}: {err}", log.hole_id, dir.display());
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+    }
+
+    // One language at a time, and at most --concurrency holes at a time
+    // within it, so a flaky API isn't hit with every request at once.
+    // (`buffered` rather than `buffer_unordered`, since the results are
+    // zipped back up with the holes they came from.)
+
+    for lang in langs.iter().filter(|_| !local) {
+        let fetched: Vec<_> = futures_util::stream::iter(&holes).map(|hole|
+            get_solution_log(client, &fetch_options, lang, &hole.id)
+        ).buffered(args.concurrency as usize).collect().await;
+
+        if let Some(code) = report_fetch_failure(&holes, lang, &fetched) {
+            return Ok(code);
+        }
+
+        for (log, solutions) in solution_logs.iter_mut().zip(fetched) {
+            log.solutions.extend(solutions.unwrap());
+        }
+    }
+
+    // With --flip-lang, the other language's logs are kept separate so that
+    // both leaderboards can be recreated side by side.
+
+    let mut flip_logs: Vec<SolutionLog> = vec![];
+
+    if let Some(flip_lang) = &args.flip_lang {
+        let fetched: Vec<_> = futures_util::stream::iter(&holes).map(|hole|
+            get_solution_log(client, &fetch_options, flip_lang, &hole.id)
+        ).buffered(args.concurrency as usize).collect().await;
+
+        if let Some(code) = report_fetch_failure(&holes, flip_lang, &fetched) {
+            return Ok(code);
+        }
+
+        flip_logs = solution_logs.iter().zip(fetched).map(|(log, solutions)|
+            SolutionLog {solutions: solutions.unwrap(), ..log.clone()}
+        ).collect();
+    }
+
+    // With --rival, <them> is whoever is just above me in the standings
+    // (or, if I'm on top, just below me). These are added up from the logs
+    // fetched for this report, rather than fetched from the site.
+
+    if args.rival {
+        let standings = overall_standings(&solution_logs, &args.scoring, cutoff_at);
+
+        let Some(place) = standings.iter().position(|(golfer, _)| *golfer == golfers[0]) else {
+            eprintln!("{} doesn't have any {} solutions to find a rival by.", golfers[0], args.lang);
+            return Ok(ExitCode::FAILURE);
+        };
+
+        let Some((rival, _)) = standings.get(place.checked_sub(1).unwrap_or(1)) else {
+            eprintln!("Nobody else has any {} solutions to be a rival.", args.lang);
+            return Ok(ExitCode::FAILURE);
+        };
+
+        status(&format!("Your rival is {rival}, #{} in the standings to your #{}.", place.checked_sub(1).unwrap_or(1) + 1, place + 1));
+
+        golfers[1] = rival.clone();
+
+        if repeated_golfer(&golfers).is_some() {
+            eprintln!("Your rival turned out to be {rival}, who's also the --reference golfer, so there'd be nothing to compare.");
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
+
+        for log in solution_logs.iter_mut().chain(&mut flip_logs) {
+            log.golfers = golfers.clone();
+        }
+    }
+
+    // Everything from here on gets printed to stdout, or with --output, to
+    // the file.
+
+    let mut sink: Box<dyn Write> = match &args.output {
+        Some(output) => Box::new(std::fs::File::create(output)?),
+        None         => Box::new(std::io::stdout()),
+    };
+
+    if args.history.is_some() {
+        write!(sink, "{}", render_history(&solution_logs[0], &golfers[..2], &args.scoring, cutoff_at, hole_name_width))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.timeline.is_some() {
+        write!(sink, "{}", render_timeline(&solution_logs[0], &golfers[..2], &args.scoring, cutoff_at))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Sanity-check the shape of the data, in case the API has changed out
+    // from under us in a way that still parses but would quietly give
+    // wrong results.
+
+    if let Some(log) = solution_logs.iter().find(|log| !log.solutions.is_empty()) {
+        for problem in schema_problems(&log.solutions) {
+            status(&format!("{yellow}Warning:{reset} the {} solution log looks unexpected ({problem}); the code.golf API may have changed, so this report may be wrong", log.hole_id));
+        }
+    }
 
     // Debug.
     
@@ -170,121 +1288,288 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     for date in dates {
         if date == cutoff {
-            println!("{date} ——————————————————————————————————————————————————————————");
+            println!("{date} {}", glyphs().rule.repeat(58));
         } else {
             println!("{date}");
         }
     }
 
-    return Ok(());
+    return Ok(ExitCode::SUCCESS);
     */
 
     // Process the data.
 
-    println!("Processing data...");
+    status("Processing data...");
 
     let before = std::time::Instant::now();
+    let processing = tracing::info_span!("processing").entered();
 
-    for log in &mut solution_logs {
+    // A solution with no submission date can't be placed relative to a
+    // cutoff, so historical reports leave it out rather than guess. (Current
+    // reports keep it, since everything the API gives us is current.)
 
-        // Give each solution an unqualified "length" which is its length
-        // in either bytes or chars depending on the scoring method we
-        // care about.
+    if historical {
+        for log in &mut solution_logs {
+            log.solutions.retain(|solution| solution.submitted_at.is_some());
+        }
+    }
 
-        for solution in &mut log.solutions {
-            solution.length = match &*args.scoring {
-                "bytes" => solution.bytes,
-                "chars" => solution.chars,
-                _ => panic!("invalid scoring criterion: '{}'", args.scoring),
-            }
+    // With --submitted-after/--submitted-before, only solutions submitted
+    // within the window count at all. A bare date means the start of that
+    // year/month/day for both ends, so "after 2025-03, before 2025-04" is
+    // exactly March.
+
+    if windowed {
+        for log in &mut solution_logs {
+            log.solutions.retain(|solution|
+                after_at .is_none_or(|after|  solution.submitted_at >= Some(after)) &&
+                before_at.is_none_or(|before| solution.submitted_at <  Some(before))
+            );
         }
+    }
 
-        // Keep only the solutions with the correct scoring method which
-        // were submitted before the cutoff.
+    let unprocessed = args.explain.as_ref().map(|_| solution_logs[0].clone());
 
-        log.solutions.retain(|solution| solution.scoring == args.scoring);
-        log.solutions.retain(|solution| solution.submitted <= cutoff);
+    // With --since (or --flipped-since), the same logs get recreated at
+    // both dates.
 
-        // Filter down to only each golfer's best submission. This gives
-        // us the submissions which were "active" at the cutoff time.
+    let mut since_logs = vec![];
 
-        log.solutions. sort_by_key(|solution| solution.length);
-        log.solutions. sort_by_key(|solution| solution.golfer.clone());
-        log.solutions.dedup_by_key(|solution| solution.golfer.clone());
+    if let Some(since_at) = since_at {
+        since_logs = solution_logs.clone();
 
-        // Sort the solutions and assign ranks, scores, and medals to them.
-        // This recreates the leaderboard as-it-was in its entirety.
+        for log in &mut since_logs {
+            process_log(log, &args.scoring, since_at, &golfers);
+        }
+    }
+
+    if args.compare_to_self_past {
+
+        // Recreate each leaderboard twice, once now and once at the cutoff,
+        // and then put my solution from back then on the current board
+        // under the past-me name. The --reference golfer (if any) is kept
+        // on the current board.
+
+        let present = [&golfers[..1], &golfers[2..]].concat();
+
+        for log in &mut solution_logs {
+            let mut past = log.clone();
 
-        log.solutions.sort_by_key(|solution| solution.submitted.clone());
-        log.solutions.sort_by_key(|solution| solution.length);
+            process_log(&mut past, &args.scoring, cutoff_at, &golfers[..1]);
+            process_log(log, &args.scoring, Utc::now(), &present);
 
-        for i in 0..log.solutions.len() {
-            log.solutions[i].score =
-                log.solutions[0].length as f32 /
-                log.solutions[i].length as f32 *
-                1000.0;
+            // Past-me is scored against today's gold, the same as everyone
+            // else on the board, so equal lengths land in the same cell.
+
+            let gold_length = log.gold_length;
+
+            log.solutions.extend(past.solutions.into_iter().map(|mut solution| {
+                solution.golfer = golfers[1].clone();
+                solution.score = gold_length as f32 / solution.length as f32 * 1000.0;
+                solution
+            }));
+        }
+    } else {
+        if both_scorings {
+            for log in &mut solution_logs {
+                let mut paired = SolutionLog {scoring: "chars".to_string(), ..log.clone()};
+                process_log(&mut paired, "chars", cutoff_at, &golfers);
+                log.paired = Some(Box::new(paired));
+            }
+        }
 
-            log.solutions[i].rank = 
-                if i > 0 && log.solutions[i].length == log.solutions[i-1].length {
-                    log.solutions[i-1].rank
-                } else {
-                    i + 1
-                };
+        for log in solution_logs.iter_mut().chain(&mut flip_logs) {
+            process_log(log, &args.scoring, cutoff_at, &golfers);
         }
+    }
+
+    drop(processing);
+    let after = std::time::Instant::now();
+
+    status(&format!("Done processing in {}ms.", (after - before).as_millis()));
+
+    if !machine_output {
+        status("");
+        status("");
+    }
+
+    if let Some(unprocessed) = &unprocessed {
+        write!(sink, "{}", render_explanation(unprocessed, &solution_logs[0], &golfers, &args.scoring, &cutoff, cutoff_at))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(since) = &args.since {
+        write!(sink, "{}", render_changes(&golfers, (since, &since_logs), (&cutoff, &solution_logs), hole_name_width))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // With --flipped-since, a hole's lead has flipped if the comparison
+    // between us came out differently back then. Not having a solution
+    // counts as losing, but a hole neither of us had solved yet had no
+    // lead to flip.
+
+    if args.flipped_since.is_some() {
+        for (log, past) in solution_logs.iter_mut().zip(&since_logs) {
+            let lead = |log: &SolutionLog| log.length_for(&golfers[0]).cmp(&log.length_for(&golfers[1]));
+            let unsolved = past.length_for(&golfers[0]) == usize::MAX && past.length_for(&golfers[1]) == usize::MAX;
 
-        if log.solutions.len() > 1 
-        && log.solutions[0].length < log.solutions[1].length {
-            log.solutions[0].rank = 0;
+            log.lead_flipped = !unsolved && lead(log) != lead(past);
         }
+    }
+
+    if ranking {
+        solution_logs.retain(|log|
+            ranked.iter().filter(|golfer| log.length_for(golfer) < usize::MAX).count() >= if args.all_holes {1} else {2}
+        );
 
-        if log.solutions.len() > 0 {
-            log.gold_length = log.solutions[0].length;
+        for log in &mut solution_logs {
+            log.bar_width = score_bar_width;
         }
 
-        // Keep only the entries from golfers we care about.
+        write!(sink, "{}", render_ranking(&solution_logs, &ranked, args.reverse))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.opponents_file.is_some() {
+        write!(sink, "{}", render_league(&solution_logs, &golfers[0], &opponents, args.all_holes))?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-        log.solutions.retain(|solution| golfers.contains(&solution.golfer));
+    if let Some(flip_lang) = &args.flip_lang {
+        write!(sink, "{}", render_flips(&golfers, (&args.lang, &solution_logs), (flip_lang, &flip_logs), hole_name_width))?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let after = std::time::Instant::now();
+    if args.opportunities {
+        for log in &mut solution_logs {
+            log.bar_width = score_bar_width;
+        }
 
-    println!("Done processing in {}ms.", (after - before).as_millis());
-    println!();
-    println!();
+        write!(sink, "{}", render_opportunities(&solution_logs, &golfers))?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    // Keep only the holes for which both <me> and <them> have made submissions.
+    // With --stats, count the holes neither of us has solved while they're
+    // all still here.
 
-    solution_logs.retain(|log|
-        log.length_for(&golfers[0]) < usize::MAX &&
-        log.length_for(&golfers[1]) < usize::MAX
+    let unattempted = args.stats.then(||
+        solution_logs.iter().filter(|log|
+            log.length_for(&golfers[0]) == usize::MAX &&
+            log.length_for(&golfers[1]) == usize::MAX
+        ).count()
     );
 
-    // Sort by how well <me> is doing compared to <them>, with a backup metric
-    // of how well I'm doing on an absolute scale.
+    // Keep only the holes for which both <me> and <them> have made submissions
+    // (or, with --all-holes, for which at least one of them has).
 
-    solution_logs.sort_by_key(|log|
-        log.sort_score(&golfers[0])
-    );
+    let solved_by = |golfer: &String| solution_logs.iter().filter(|log| log.length_for(golfer) < usize::MAX).count();
+    let solved = (solved_by(&golfers[0]), solved_by(&golfers[1]));
 
-    solution_logs.sort_by_key(|log|
-        log.sort_score(&golfers[0]) as isize -
-        log.sort_score(&golfers[1]) as isize
+    solution_logs.retain(|log|
+        if args.all_holes {
+            log.length_for(&golfers[0]) < usize::MAX ||
+            log.length_for(&golfers[1]) < usize::MAX
+        } else {
+            log.length_for(&golfers[0]) < usize::MAX &&
+            log.length_for(&golfers[1]) < usize::MAX
+        }
     );
 
-    if !args.reverse {
+    let shared = solution_logs.len();
+
+    // With --active-within, keep only the holes that one of us has improved
+    // on recently.
+
+    if let Some(window) = args.active_within {
+        let since = Utc::now() - window;
+
+        solution_logs.retain(|log|
+            log.solutions.iter().any(|sol| golfers[..2].contains(&sol.golfer) && sol.submitted_at.is_some_and(|at| at >= since))
+        );
+    }
+
+    // With --exclude-gold-draws, drop the holes where we're both tied at the
+    // gold length, since there's nothing left to play for on those.
+
+    if args.exclude_gold_draws {
+        solution_logs.retain(|log|
+            log.length_for(&golfers[0]) != log.gold_length ||
+            log.length_for(&golfers[1]) != log.gold_length
+        );
+    }
+
+    // If all that leaves nothing (an obscure language, say, or a cutoff from
+    // before either of us got going), an all-zeros report would only be
+    // confusing, so say what happened instead.
+
+    if solution_logs.is_empty() && !machine_output {
+        let filters = [(args.active_within.is_some(), "--active-within"), (args.exclude_gold_draws, "--exclude-gold-draws")]
+            .into_iter().filter_map(|(given, flag)| given.then_some(flag)).collect::<Vec<_>>();
+
+        write!(sink, "{}", render_no_holes(&golfers, &args.lang, solved, shared, &filters))?;
+
+        // Nobody's ahead on no holes, so with --exit-code margin it's a tie.
+
+        return Ok(ExitCode::from(if args.exit_code.is_some() {11} else {0}));
+    }
+
+    // By default, sort by how well <me> is doing compared to <them>, with a
+    // backup metric of how well I'm doing on an absolute scale.
+
+    match args.sort {
+        SortMode::Delta => {
+            solution_logs.sort_by_key(|log|
+                log.sort_score(&golfers[0])
+            );
+
+            solution_logs.sort_by_key(|log|
+                log.sort_score(&golfers[0]) as isize -
+                log.sort_score(&golfers[1]) as isize
+            );
+
+            solution_logs.reverse();
+        }
+        SortMode::MyScore | SortMode::TheirScore => {
+            let golfer = &golfers[if args.sort == SortMode::MyScore {0} else {1}];
+            solution_logs.sort_by_key(|log| log.sort_score(golfer));
+            solution_logs.reverse();
+        }
+        SortMode::Hole => {
+            solution_logs.sort_by(|a, b| a.label().cmp(b.label()));
+        }
+        SortMode::Category => {
+            solution_logs.sort_by(|a, b| (&a.category, a.label()).cmp(&(&b.category, b.label())));
+        }
+        SortMode::GoldMargin => {
+            solution_logs.sort_by_key(|log| log.length_for(&golfers[0]).saturating_sub(log.gold_length));
+        }
+    }
+
+    if args.reverse {
         solution_logs.reverse();
     }
 
     // Compute a bunch of stuff for formatting.
 
-    let hole_name_width = args.hole_name_width;
-    let mut bar_width = args.score_bar_width;
+    let mut bar_width = score_bar_width;
 
     let wins   = solution_logs.iter().filter(|log| log.length_for(&golfers[0]) <  log.length_for(&golfers[1])).count();
     let draws  = solution_logs.iter().filter(|log| log.length_for(&golfers[0]) == log.length_for(&golfers[1])).count();
     let losses = solution_logs.iter().filter(|log| log.length_for(&golfers[0]) >  log.length_for(&golfers[1])).count();
     let delta  = losses as isize - wins as isize;
-    let total  = wins + losses + draws;
+
+    // With --exit-code margin, the exit code says how comfortably I'm
+    // winning, so that scripts can branch on it without parsing the output.
+
+    let exit_code = match args.exit_code {
+        None => ExitCode::SUCCESS,
+        Some(ExitCodeMode::Margin) => ExitCode::from(match -delta {
+            lead if lead > args.narrow_lead as isize => 0,
+            1..                                      => 10,
+            0                                        => 11,
+            ..0                                      => 12,
+        }),
+    };
 
     let num_len = |num: usize| if num > 0 {num.ilog(10) + 1} else {1};
     let wdl_width = (num_len(wins) + num_len(draws) + num_len(losses) + 6) as usize;
@@ -297,167 +1582,100 @@ async fn main() -> Result<(), Box<dyn Error>> {
         bar_width += 1;
     }
 
-    // Compute more stuff for formatting.
-
-    cutoff = cutoff.replace("z", "");
-    let asterisk = if cutoff_provided {"*"} else {""};
+    // When comparing against my past self, the report as a whole is as of
+    // now; the cutoff is already part of the past-me name.
 
-    let empty  = "";
-    let asof   = "as of";
-    let indent = hole_name_width - (args.lang.len() + 1 + asof.chars().count() + 1 + cutoff.len() + asterisk.len());
-    let lcenter = (bar_width - wdl_width) / 2;
-    let rcenter = ((bar_width - wdl_width) + 1) / 2;
+    if args.compare_to_self_past {
+        cutoff = Utc::now().format("%Y-%m-%d").to_string();
+    }
 
-    let names_v1 = format!("{} vs. {}", golfers[0], golfers[1]);
-    let names_v2 = format!("{} v. {}", golfers[0], golfers[1]);
+    // With --normalize-scores, stretch the score bars so that my best hole
+    // in the report reaches the end of the bar, which makes the bars show
+    // how each hole compares to my best rather than to the gold. Anyone
+    // doing better than that just sits at the end.
 
-    let names = if (names_v1.len() - wdl_width) % 2 == 0 {
-        names_v1
-    } else {
-        names_v2
+    let bar_scale = match solution_logs.iter().map(|log| log.score_for(&golfers[0])).reduce(f32::max) {
+        Some(best) if args.normalize_scores && best > 0.0 => 1000.0 / best,
+        _ => 1.0,
     };
 
-    let names_indent = (hole_name_width * 2 + 4 + bar_width - names.len()) / 2;
-
     // Give the SolutionLogs the formatting info they need.
 
     for log in &mut solution_logs {
-        log.hole_name_width = hole_name_width;
         log.bar_width = bar_width;
-    }
-
-    // Print the holes.
+        log.bar_scale = bar_scale;
 
-    for log in &solution_logs {
-        println!("{log}");
+        if let Some(paired) = &mut log.paired {
+            paired.bar_width = bar_width;
+            paired.bar_scale = bar_scale;
+        }
     }
 
-    // Print the after-summary.
-
-    println!();
-    print!("{empty:indent$}{ULINE}{LLGREY}{}{RESET} {LGREY}{asof}{RESET} {LLGREY}{ULINE}{}{RESET}{LLGREY}{asterisk}{RESET}  ", args.lang, cutoff);
-    print!("{empty:lcenter$}{GREEN}{wins}{RESET} {LGREY}/{RESET} {LLLGREY}{draws}{RESET} {LGREY}/{RESET} {RED}{losses}{RESET}{empty:rcenter$}  ");
-
-    match delta {
-        1..   => print!("{BOLD}{RED}+{delta} loss{}{RESET}", if delta.abs() > 1 {"es"} else {"!"}),
-        0     => print!("Tie!!"),
-        ..=-1 => print!("{BOLD}{GREEN}+{} win{}!!!{RESET}", -delta, if delta.abs() > 1 {"s!"} else {""}),
-    };
-
-    print!(" {MLGREY}({total} holes){RESET}");
-
-    println!();
-    println!("{empty:names_indent$}{LLGREY}{names}{RESET}");
-    println!();
-    println!();
-
-    Ok(())
-}
-
-async fn get_solution_log(clean_api: bool, lang: &str, hole_id: &str) -> Vec<Solution> {
-    let url = if clean_api {
-        format!(
-            "http://code.golf/scores/{}/{}/all",
-            urlencoding::encode(hole_id),
-            urlencoding::encode(lang),
-        )
-    } else {
-        format!(
-            "http://code.golf/api/solutions-log?hole={}&lang={}",
-            urlencoding::encode(hole_id),
-            urlencoding::encode(lang),
-        )
+    let comparison = Comparison {
+        logs: solution_logs,
+        golfers,
+        lang: args.lang.clone(),
+        scoring: args.scoring.clone(),
+        scoring_mode: if both_scorings {"both".to_string()} else if points {"points".to_string()} else {args.scoring.clone()},
+        cutoff,
+        historical,
+        compare_to_self_past: args.compare_to_self_past,
+        near: args.near,
+        unattempted,
+        mini_board: args.mini_board,
+        links: args.links,
+        group_by_category: args.group_by_category,
+        summary_format: args.summary_format,
+        only: [(args.only_wins, Ordering::Less), (args.only_ties, Ordering::Equal), (args.only_losses, Ordering::Greater)]
+            .into_iter().find_map(|(only, ordering)| only.then_some(ordering)),
+        min_delta: args.min_delta,
+        limit: args.limit,
+        hole_name_width,
+        bar_width,
     };
 
-    for _attempt in 0..10 {
-        let resp = reqwest::get(&url).await.unwrap();
-        if !resp.status().is_success() {continue;}
-        let text = resp.text().await.unwrap();
+    // With --output-dir, write each of the requested formats to its own
+    // file (named after the cutoff) instead of printing a report.
 
-        let mut ret: Vec<Solution> = serde_json::from_str(&text).expect("could not parse solution log");
+    if let Some(output_dir) = &args.output_dir {
+        std::fs::create_dir_all(output_dir)?;
 
-        // Fix up the dates to look like "2025-03-31 12:15:17.129587".
+        let formats = if args.formats.is_empty() {vec![format]} else {args.formats.clone()};
+        let stem = comparison.cutoff.replace(" ", "_").replace(":", "-");
 
-        for sol in &mut ret {
-            sol.submitted = sol.submitted.replace("T", " ").replace("Z", "");
+        for format in formats {
+            let path = std::path::Path::new(output_dir).join(format!("{stem}.{}", format.extension()));
+            std::fs::write(&path, comparison.render(format)?)?;
+            status(&format!("Wrote {}", path.display()));
         }
 
-        return ret;
+        return Ok(exit_code);
     }
 
-    panic!("When fetching solutions log for hole \"{hole_id}\", the code.golf API gave a non-2XX status code for 10 attempts in a row. The code.golf API is a little unstable, so you might just try re-running the script.");
-}
-
-impl fmt::Display for SolutionLog {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{LLLLGREY}{:>1$}{RESET}  ", self.hole_id, self.hole_name_width)?;
-
-        let mut markers: Vec<(String, usize)> = vec![];
+    write!(sink, "{}", comparison.render(format)?)?;
 
-        for sol in &self.solutions {
-            let sigil = format!(
-                "{BOLD}{}{}{RESET}",
-                [GREEN, BROWN, BLUE][self.golfers.iter().position(|i|i==&sol.golfer).unwrap()],
-                sol.golfer.chars().next().unwrap(),
-            );
-
-            let mut shift = (sol.score / 1000.0 * (self.bar_width-1) as f32) as usize;
-            while markers.iter().any(|marker| marker.1 == shift) {
-                shift -= 1;
-            }
-
-            markers.push((sigil, shift));
-        }
+    if let Some(output) = &args.output {
+        status(&format!("Wrote {output}"));
+    }
 
-        for i in 0..self.bar_width {
-            write!(
-                f, "{}",
-                markers.iter()
-                       .find(|marker| marker.1 == i)
-                       .map(|marker| marker.0.clone())
-                       .unwrap_or(format!("{GREY}—{RESET}"))
-            )?;
-        }
+    Ok(exit_code)
+}
 
-        let len_0 = self.length_for(&self.golfers[0]);
-        let len_1 = self.length_for(&self.golfers[1]);
-        let delta = len_0 as isize - len_1 as isize;
+// Say which hole's solution log (if any) couldn't be fetched, and what
+// exit code to give up with.
 
-        match delta {
-            ..0 => write!(f, "  {DIM}{GREEN}{delta} {}{}{RESET}", &self.scoring[..4], if delta.abs() > 1 {"s"} else {""})?,
-            1.. => write!(f, "  {DIM}{RED}+{delta} {}{}{RESET}",  &self.scoring[..4], if delta.abs() > 1 {"s"} else {""})?,
-             0  => write!(f, "  {MLGREY}Tie{}{RESET}", if len_0 == self.gold_length {"d gold"} else {""})?,
-        };
+fn report_fetch_failure(holes: &[Hole], lang: &str, fetched: &[Result<Vec<Solution>, FetchError>]) -> Option<ExitCode> {
+    let (hole, err) = holes.iter().zip(fetched).find_map(|(hole, result)| result.as_ref().err().map(|err| (hole, err)))?;
 
-        write!(
-            f, " {MGREY}({}-{}|{}){RESET}",
-            self.length_for(&self.golfers[0]),
-            self.length_for(&self.golfers[1]),
-            self.gold_length,
-        )?;
+    eprintln!("Couldn't fetch the {lang} solution log for hole \"{}\": {err}.", hole.id);
 
-        Ok(())
+    match err {
+        FetchError::Dump(_) => {}
+        _ if err.is_transient() => eprintln!("This is usually a temporary problem, so you might just try re-running."),
+        _ => eprintln!("The code.golf API may have changed in a way this tool doesn't understand yet."),
     }
-}
 
-impl SolutionLog {
-    fn sort_score(&self, golfer: &str) -> usize {
-        self.solutions
-            .iter()
-            .find(|solution| solution.golfer == golfer)
-            .map(|solution|
-                (solution.score * 10000.0) as usize
-                + if solution.rank == 0 {1} else {0}
-            )
-            .unwrap_or(0)
-    }
-
-    fn length_for(&self, golfer: &str) -> usize {
-        self.solutions
-            .iter()
-            .find(|solution| solution.golfer == golfer)
-            .map(|solution| solution.length)
-            .unwrap_or(usize::MAX)
-    }
+    Some(ExitCode::FAILURE)
 }
 
+
@@ -0,0 +1,149 @@
+// The holes and solutions the code.golf API hands back, and the per-hole
+// solution logs the rest of the program works from.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use clap::ValueEnum;
+use serde::{Serialize, Deserialize, Deserializer};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hole {
+    pub category: String,
+    pub id: String,
+    pub name: String,
+    pub preamble: String,
+    pub links: Vec<HoleLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoleLink {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lang {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Solution {
+    pub bytes: usize,
+    pub chars: usize,
+    #[serde(alias = "login")] pub golfer: String,
+    pub hole: String,
+    pub lang: String,
+    pub scoring: String,
+    #[serde(default, deserialize_with = "null_as_empty")] pub submitted: String,
+    #[serde(skip)] pub submitted_at: Option<DateTime<Utc>>,     // Parsed from submitted.
+
+    #[serde(default)] pub length: usize,    // Copy of bytes or chars.
+    #[serde(default)] pub rank: usize,      // Computed by us.
+    #[serde(default)] pub score: f32,       // Computed by us.
+}
+
+#[derive(Clone, Default)]
+pub struct SolutionLog {
+    pub hole_id: String,
+    pub hole_name: String,
+    pub category: String,
+    pub url: String,                    // The hole's page on code.golf.
+    pub links: Vec<HoleLink>,
+    pub solutions: Vec<Solution>,
+    pub leaderboard: Vec<Solution>,     // Everyone's, not just the golfers'.
+    pub gold_length: usize,
+    pub golfers: Vec<String>,
+    pub scoring: String,
+    pub hole_name_width: usize,
+    pub bar_width: usize,
+    pub bar_style: BarStyle,
+    pub bar_rounding: BarRounding,
+    pub bar_scale: f32,                 // Set by --normalize-scores.
+    pub delta_percent: bool,
+    pub show_id: bool,
+    pub no_truncate: bool,
+    pub medals: bool,
+    pub field_rank: bool,
+    pub show_dates: bool,
+    pub lead_flipped: bool,             // Set by --flipped-since.
+    pub reference: Option<String>,      // Just drawn on the bar, faintly.
+    pub points: bool,
+    pub paired: Option<Box<SolutionLog>>,   // The same hole by chars, with --scoring both.
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BarStyle {
+    #[default]
+    Letters,
+    Blocks,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BarRounding {
+    #[default]
+    Nearest,
+    Truncate,
+}
+
+// Treat a null string field the same as a missing one.
+
+pub fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+// Parse the dates (so they can be compared as instants, whatever their
+// precision or time zone), and fix them up to look like
+// "2025-03-31 12:15:17.129587" for display.
+
+pub fn fix_up_dates(solutions: &mut [Solution]) {
+    for sol in solutions {
+        sol.submitted_at = DateTime::parse_from_rfc3339(&sol.submitted).ok()
+            .map(|at| at.to_utc())
+            .or_else(|| NaiveDateTime::parse_from_str(&sol.submitted, "%Y-%m-%d %H:%M:%S%.f").ok().map(|at| at.and_utc()));
+
+        sol.submitted = sol.submitted.replace("T", " ").replace("Z", "");
+    }
+}
+
+impl SolutionLog {
+    // What to call the hole in the report: its name, or with --show-id,
+    // its ID.
+
+    pub fn label(&self) -> &str {
+        if self.show_id || self.hole_name.is_empty() {&self.hole_id} else {&self.hole_name}
+    }
+
+    pub fn sort_score(&self, golfer: &str) -> usize {
+        self.solutions
+            .iter()
+            .find(|solution| solution.golfer == golfer)
+            .map(|solution|
+                (solution.score * 10000.0) as usize
+                + if solution.rank == 0 {1} else {0}
+            )
+            .unwrap_or(0)
+    }
+
+    pub fn score_for(&self, golfer: &str) -> f32 {
+        self.solutions
+            .iter()
+            .find(|solution| solution.golfer == golfer)
+            .map(|solution| solution.score)
+            .unwrap_or(0.0)
+    }
+
+    // The golfer's points for the hole under --scoring points, which is
+    // their score rounded to a whole number.
+
+    pub fn points_for(&self, golfer: &str) -> usize {
+        self.score_for(golfer).round() as usize
+    }
+
+    pub fn length_for(&self, golfer: &str) -> usize {
+        self.solutions
+            .iter()
+            .find(|solution| solution.golfer == golfer)
+            .map(|solution| solution.length)
+            .unwrap_or(usize::MAX)
+    }
+}
@@ -0,0 +1,440 @@
+// Recreating each hole's leaderboard as of the cutoff, and the date
+// handling and sanity checks that go with it.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveDateTime, Utc};
+use regex::Regex;
+
+use crate::model::*;
+
+// Recreate a hole's leaderboard as it stood at the cutoff, then narrow it
+// down to just the golfers we care about.
+
+pub fn process_log(log: &mut SolutionLog, scoring: &str, cutoff_at: DateTime<Utc>, golfers: &[String]) {
+
+    // Give each solution an unqualified "length" which is its length
+    // in either bytes or chars depending on the scoring method we
+    // care about.
+
+    for solution in &mut log.solutions {
+        solution.length = match scoring {
+            "bytes" => solution.bytes,
+            "chars" => solution.chars,
+            _ => panic!("invalid scoring criterion: '{}'", scoring),
+        }
+    }
+
+    // Keep only the solutions with the correct scoring method which
+    // were submitted before the cutoff.
+
+    log.solutions.retain(|solution| solution.scoring == scoring);
+    log.solutions.retain(|solution| solution.submitted_at.is_none_or(|at| at < cutoff_at));
+
+    // Filter down to only each golfer's best submission: their shortest,
+    // and of those, the one they submitted first (which is when they
+    // actually got that length). This gives us the submissions which were
+    // "active" at the cutoff time.
+
+    log.solutions.sort_by(|a, b|
+        a.golfer.cmp(&b.golfer)
+            .then(a.length.cmp(&b.length))
+            .then(a.submitted_at.cmp(&b.submitted_at))
+    );
+
+    log.solutions.dedup_by(|a, b| a.golfer == b.golfer);
+
+    // Sort the solutions and assign ranks, scores, and medals to them.
+    // This recreates the leaderboard as-it-was in its entirety, where
+    // whoever got a length first is listed first.
+
+    log.solutions.sort_by_key(|solution| (solution.length, solution.submitted_at));
+
+    for i in 0..log.solutions.len() {
+        log.solutions[i].score =
+            log.solutions[0].length as f32 /
+            log.solutions[i].length as f32 *
+            1000.0;
+
+        log.solutions[i].rank = 
+            if i > 0 && log.solutions[i].length == log.solutions[i-1].length {
+                log.solutions[i-1].rank
+            } else {
+                i + 1
+            };
+    }
+
+    if log.solutions.len() > 1 
+    && log.solutions[0].length < log.solutions[1].length {
+        log.solutions[0].rank = 0;
+    }
+
+    if !log.solutions.is_empty() {
+        log.gold_length = log.solutions[0].length;
+    }
+
+    // Keep only the entries from golfers we care about (but hang onto the
+    // whole leaderboard for the views that show it).
+
+    log.leaderboard = log.solutions.clone();
+    log.solutions.retain(|solution| golfers.contains(&solution.golfer));
+}
+
+// Everyone's total score over the given holes as of the cutoff, best first
+// (and by name among equals). This is worked out from the logs we've
+// fetched, so it's only the site's overall standings if every hole is in.
+
+pub fn overall_standings(logs: &[SolutionLog], scoring: &str, cutoff_at: DateTime<Utc>) -> Vec<(String, f32)> {
+    let mut totals: HashMap<String, f32> = HashMap::new();
+
+    for log in logs {
+        let mut log = log.clone();
+        process_log(&mut log, scoring, cutoff_at, &[]);
+
+        for solution in &log.leaderboard {
+            *totals.entry(solution.golfer.clone()).or_default() += solution.score;
+        }
+    }
+
+    let mut totals: Vec<(String, f32)> = totals.into_iter().collect();
+    totals.sort_by(|(golfer_a, a), (golfer_b, b)| b.total_cmp(a).then(golfer_a.cmp(golfer_b)));
+    totals
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CutoffType {IncludeEnd, ExcludeEnd}
+use CutoffType::*;
+
+// Work out which of the accepted date formats a date is in, if any. Dates
+// without a time include the whole year/month/day they name.
+
+static DATE_REGEXES: LazyLock<[(CutoffType, Regex); 6]> = LazyLock::new(|| [
+    (IncludeEnd, Regex::new(r"^\d\d\d\d$").unwrap()),
+    (IncludeEnd, Regex::new(r"^\d\d\d\d-\d\d$").unwrap()),
+    (IncludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d$").unwrap()),
+    (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d$").unwrap()),
+    (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d:\d\d$").unwrap()),
+    (ExcludeEnd, Regex::new(r"^\d\d\d\d-\d\d-\d\d \d\d:\d\d:\d\d\.\d+$").unwrap()),
+]);
+
+pub fn date_format(date: &str) -> Option<CutoffType> {
+    DATE_REGEXES.iter()
+        .find(|(_cutoff_type, regex)| regex.is_match(date))
+        .map(|(cutoff_type, _regex)| *cutoff_type)
+}
+
+// The span of time a date (in one of the formats date_format accepts)
+// covers: from when it starts up to (but not including) when it ends. A
+// date with a time is just an instant, so it ends as soon as it starts.
+
+pub fn date_bounds(date: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    match date_format(date)? {
+        IncludeEnd => {
+            let start = match date.len() {
+                4 => NaiveDate::from_ymd_opt(date.parse().ok()?, 1, 1)?,
+                7 => NaiveDate::parse_from_str(&format!("{date}-01"), "%Y-%m-%d").ok()?,
+                _ => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+            };
+
+            let end = match date.len() {
+                4 => start.with_year(start.year() + 1)?,
+                7 => start.checked_add_months(Months::new(1))?,
+                _ => start.succ_opt()?,
+            };
+
+            Some((start.and_hms_opt(0, 0, 0)?.and_utc(), end.and_hms_opt(0, 0, 0)?.and_utc()))
+        }
+
+        ExcludeEnd => {
+            let format = if date.len() == "2025-03-31 12:15".len() {"%Y-%m-%d %H:%M"} else {"%Y-%m-%d %H:%M:%S%.f"};
+            let instant = NaiveDateTime::parse_from_str(date, format).ok()?.and_utc();
+
+            Some((instant, instant))
+        }
+    }
+}
+
+// Drop any hole the catalog lists more than once (keeping the first), which
+// would otherwise get it fetched and reported twice. Returns how many
+// entries were dropped.
+
+pub fn dedup_holes(holes: &mut Vec<Hole>) -> usize {
+    let mut seen_ids = HashSet::new();
+    let count = holes.len();
+
+    holes.retain(|hole| seen_ids.insert(hole.id.clone()));
+    count - holes.len()
+}
+
+// Find a golfer who's been named twice (say, as both <me> and <them>),
+// ignoring case the way code.golf logins do. Comparing someone against
+// themself ties every hole, which just looks like a bug.
+
+pub fn repeated_golfer(golfers: &[String]) -> Option<(&String, &String)> {
+    golfers.iter().enumerate().find_map(|(i, golfer)|
+        golfers[..i].iter().find(|earlier| earlier.eq_ignore_ascii_case(golfer)).map(|earlier| (earlier, golfer))
+    )
+}
+
+// Look for signs that solution data doesn't have the shape we expect.
+
+pub fn schema_problems(solutions: &[Solution]) -> Vec<String> {
+    let mut problems = vec![];
+
+    if solutions.iter().any(|sol| sol.golfer.is_empty()) {
+        problems.push("some solutions have no golfer".to_string());
+    }
+
+    if solutions.iter().all(|sol| sol.scoring != "bytes" && sol.scoring != "chars") {
+        problems.push("no solutions are scored in bytes or chars".to_string());
+    }
+
+    if solutions.iter().all(|sol| sol.bytes == 0 && sol.chars == 0) {
+        problems.push("all solutions have zero length".to_string());
+    }
+
+    // A missing date is fine (those solutions just stay out of historical
+    // reports), but one that's there and can't be read is a new format.
+
+    if solutions.iter().any(|sol| !sol.submitted.is_empty() && sol.submitted_at.is_none()) {
+        problems.push("some solutions have a submission date in a format we don't recognize".to_string());
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(golfer: &str, length: usize, submitted: &str) -> Solution {
+        Solution {
+            bytes: length,
+            chars: length,
+            golfer: golfer.to_string(),
+            scoring: "bytes".to_string(),
+            submitted_at: Some(at(submitted)),
+            ..Default::default()
+        }
+    }
+
+    fn at(date: &str) -> DateTime<Utc> {
+        format!("{date}Z").parse().unwrap()
+    }
+
+    fn processed(solutions: Vec<Solution>, cutoff: &str, golfers: &[&str]) -> SolutionLog {
+        let golfers: Vec<String> = golfers.iter().map(|golfer| golfer.to_string()).collect();
+        let mut log = SolutionLog {solutions, gold_length: usize::MAX, ..Default::default()};
+
+        process_log(&mut log, "bytes", at(cutoff), &golfers);
+        log
+    }
+
+    fn rank_of(log: &SolutionLog, golfer: &str) -> usize {
+        log.leaderboard.iter().find(|sol| sol.golfer == golfer).unwrap().rank
+    }
+
+    #[test]
+    fn sole_gold_is_rank_zero() {
+        let log = processed(vec![
+            solution("a", 40, "2025-01-01T00:00:00"),
+            solution("b", 50, "2025-01-01T00:00:00"),
+            solution("c", 60, "2025-01-01T00:00:00"),
+        ], "2026-01-01T00:00:00", &["a", "b"]);
+
+        assert_eq!(rank_of(&log, "a"), 0);
+        assert_eq!(rank_of(&log, "b"), 2);
+        assert_eq!(rank_of(&log, "c"), 3);
+        assert_eq!(log.gold_length, 40);
+    }
+
+    #[test]
+    fn dates_without_a_time_include_the_whole_span() {
+        assert_eq!(date_format("2025"), Some(IncludeEnd));
+        assert_eq!(date_bounds("2025"), Some((at("2025-01-01T00:00:00"), at("2026-01-01T00:00:00"))));
+        assert_eq!(date_bounds("2025-02"), Some((at("2025-02-01T00:00:00"), at("2025-03-01T00:00:00"))));
+        assert_eq!(date_bounds("2025-12"), Some((at("2025-12-01T00:00:00"), at("2026-01-01T00:00:00"))));
+        assert_eq!(date_bounds("2024-02-29"), Some((at("2024-02-29T00:00:00"), at("2024-03-01T00:00:00"))));
+    }
+
+    #[test]
+    fn dates_with_a_time_are_instants() {
+        assert_eq!(date_format("2025-03-31 12:15"), Some(ExcludeEnd));
+        assert_eq!(date_bounds("2025-03-31 12:15"), Some((at("2025-03-31T12:15:00"), at("2025-03-31T12:15:00"))));
+        assert_eq!(date_bounds("2025-03-31 12:15:29"), Some((at("2025-03-31T12:15:29"), at("2025-03-31T12:15:29"))));
+        assert_eq!(date_bounds("2025-03-31 12:15:29.185779"), Some((at("2025-03-31T12:15:29.185779"), at("2025-03-31T12:15:29.185779"))));
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        for date in ["2025-02-30", "2025-13", "2025-03-31 25:00", "2025-03-31 12:15:29x185779", "25-03-31", "2025-3-31", "soon"] {
+            assert_eq!(date_bounds(date), None, "{date}");
+        }
+
+        assert_eq!(date_format("2025-03-31 12:15:29x185779"), None);
+    }
+
+    #[test]
+    fn duplicate_holes_are_listed_once() {
+        let hole = |id: &str, name: &str| Hole {
+            category: "Sequence".to_string(),
+            id: id.to_string(),
+            name: name.to_string(),
+            preamble: String::new(),
+            links: vec![],
+        };
+
+        let mut holes = vec![hole("fizz-buzz", "Fizz Buzz"), hole("pi", "π"), hole("fizz-buzz", "Fizz Buzz (again)")];
+
+        assert_eq!(dedup_holes(&mut holes), 1);
+
+        let names: Vec<&str> = holes.iter().map(|hole| hole.name.as_str()).collect();
+        assert_eq!(names, ["Fizz Buzz", "π"]);
+    }
+
+    #[test]
+    fn best_submission_is_shortest_then_earliest() {
+        let log = processed(vec![
+            solution("a", 45, "2025-01-01T00:00:00"),
+            solution("a", 40, "2025-03-01T00:00:00"),
+            solution("a", 40, "2025-02-01T00:00:00"),
+            solution("a", 42, "2025-01-15T00:00:00"),
+            solution("b", 40, "2025-02-15T00:00:00"),
+        ], "2026-01-01T00:00:00", &["a", "b"]);
+
+        let best: Vec<_> = log.solutions.iter().map(|sol| (sol.golfer.as_str(), sol.length, sol.submitted_at)).collect();
+
+        assert_eq!(best, [
+            ("a", 40, Some(at("2025-02-01T00:00:00"))),
+            ("b", 40, Some(at("2025-02-15T00:00:00"))),
+        ]);
+    }
+
+    #[test]
+    fn ties_share_a_rank() {
+        let log = processed(vec![
+            solution("a", 40, "2025-01-01T00:00:00"),
+            solution("b", 40, "2025-01-02T00:00:00"),
+            solution("c", 50, "2025-01-01T00:00:00"),
+            solution("d", 50, "2025-01-01T00:00:00"),
+            solution("e", 60, "2025-01-01T00:00:00"),
+        ], "2026-01-01T00:00:00", &["a", "b"]);
+
+        // A shared gold isn't a sole gold, so nobody gets rank 0.
+
+        assert_eq!(rank_of(&log, "a"), 1);
+        assert_eq!(rank_of(&log, "b"), 1);
+        assert_eq!(rank_of(&log, "c"), 3);
+        assert_eq!(rank_of(&log, "d"), 3);
+        assert_eq!(rank_of(&log, "e"), 5);
+    }
+
+    #[test]
+    fn only_best_solution_before_cutoff_counts() {
+        let log = processed(vec![
+            solution("a", 60, "2025-01-01T00:00:00"),
+            solution("a", 50, "2025-02-01T00:00:00"),
+            solution("a", 40, "2025-03-01T00:00:00"),
+            solution("b", 45, "2025-01-15T00:00:00"),
+        ], "2025-03-01T00:00:00", &["a", "b"]);
+
+        // Submitted exactly at the cutoff is too late.
+
+        assert_eq!(log.length_for("a"), 50);
+        assert_eq!(log.length_for("b"), 45);
+        assert_eq!(rank_of(&log, "b"), 0);
+    }
+
+    #[test]
+    fn solutions_without_dates_are_kept() {
+        let mut undated = solution("a", 40, "2025-01-01T00:00:00");
+        undated.submitted_at = None;
+
+        let log = processed(vec![
+            undated,
+            solution("b", 45, "2030-01-01T00:00:00"),
+        ], "2026-01-01T00:00:00", &["a", "b"]);
+
+        assert_eq!(log.length_for("a"), 40);
+        assert_eq!(log.length_for("b"), usize::MAX);
+    }
+
+    #[test]
+    fn null_and_missing_dates_parse_as_undated() {
+        let mut solutions: Vec<Solution> = serde_json::from_str(r#"[
+            {"bytes": 40, "chars": 40, "login": "a", "hole": "pi", "lang": "c", "scoring": "bytes", "submitted": null},
+            {"bytes": 45, "chars": 45, "login": "b", "hole": "pi", "lang": "c", "scoring": "bytes"},
+            {"bytes": 50, "chars": 50, "login": "c", "hole": "pi", "lang": "c", "scoring": "bytes", "submitted": "2025-01-01T00:00:00Z"}
+        ]"#).unwrap();
+
+        fix_up_dates(&mut solutions);
+
+        let dates: Vec<_> = solutions.iter().map(|sol| (sol.submitted.as_str(), sol.submitted_at)).collect();
+        assert_eq!(dates, [("", None), ("", None), ("2025-01-01 00:00:00", Some(at("2025-01-01T00:00:00")))]);
+
+        // That's tolerated, not taken as a sign of the API changing.
+
+        assert!(schema_problems(&solutions).is_empty());
+
+        solutions[2].submitted = "yesterday".to_string();
+        solutions[2].submitted_at = None;
+        assert_eq!(schema_problems(&solutions).len(), 1);
+    }
+
+    #[test]
+    fn other_scoring_and_other_golfers_are_dropped() {
+        let mut chars = solution("a", 10, "2025-01-01T00:00:00");
+        chars.scoring = "chars".to_string();
+
+        let log = processed(vec![
+            chars,
+            solution("a", 50, "2025-01-01T00:00:00"),
+            solution("b", 25, "2025-01-01T00:00:00"),
+            solution("c", 100, "2025-01-01T00:00:00"),
+        ], "2026-01-01T00:00:00", &["a", "c"]);
+
+        assert_eq!(log.solutions.len(), 2);
+        assert_eq!(log.leaderboard.len(), 3);
+        assert_eq!(log.gold_length, 25);
+        assert_eq!(log.score_for("a"), 500.0);
+        assert_eq!(log.score_for("c"), 250.0);
+    }
+
+    #[test]
+    fn repeated_golfers_are_caught_regardless_of_case() {
+        let golfers = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(repeated_golfer(&golfers(&["acotis", "DialFrost"])), None);
+        assert_eq!(repeated_golfer(&golfers(&["acotis", "DialFrost", "JayXon"])), None);
+
+        let same = golfers(&["acotis", "Acotis"]);
+        assert_eq!(repeated_golfer(&same), Some((&same[0], &same[1])));
+
+        let reference = golfers(&["acotis", "DialFrost", "dialfrost"]);
+        assert_eq!(repeated_golfer(&reference), Some((&reference[1], &reference[2])));
+    }
+
+    #[test]
+    fn standings_add_up_scores_across_holes() {
+        let hole = |solutions| SolutionLog {solutions, gold_length: usize::MAX, ..Default::default()};
+
+        let logs = vec![
+            hole(vec![
+                solution("a", 50, "2025-01-01T00:00:00"),
+                solution("b", 100, "2025-01-01T00:00:00"),
+            ]),
+            hole(vec![
+                solution("b", 40, "2025-01-01T00:00:00"),
+                solution("c", 80, "2025-01-01T00:00:00"),
+            ]),
+        ];
+
+        let standings = overall_standings(&logs, "bytes", at("2026-01-01T00:00:00"));
+
+        assert_eq!(standings, vec![
+            ("b".to_string(), 1500.0),
+            ("a".to_string(), 1000.0),
+            ("c".to_string(), 500.0),
+        ]);
+    }
+}
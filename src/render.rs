@@ -0,0 +1,1625 @@
+// Turning processed solution logs into reports, in every format the
+// program can print.
+
+use std::fmt;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::io::IsTerminal;
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::model::*;
+use crate::process::{CutoffType::*, date_format};
+
+// Terminal colors and styles. Everything the program prints gets its colors
+// from here, so that they can all be switched off in one place (for
+// NO_COLOR, --no-color, or when stdout isn't a terminal).
+
+pub struct Palette {
+    pub bold:     &'static str,
+    pub dim:      &'static str,
+    pub uline:    &'static str,
+    pub green:    &'static str,
+    pub red:      &'static str,
+    pub yellow:   &'static str,
+    pub brown:    &'static str,
+    pub blue:     &'static str,
+    pub grey:     &'static str,
+    pub mgrey:    &'static str,
+    pub lgrey:    &'static str,
+    pub mlgrey:   &'static str,
+    pub llgrey:   &'static str,
+    pub lllgrey:  &'static str,
+    pub llllgrey: &'static str,
+    pub reset:    &'static str,
+}
+
+const ANSI_PALETTE: Palette = Palette {
+    bold:     "\x1b[1m",
+    dim:      "\x1b[2m",
+    uline:    "\x1b[4m",
+    green:    "\x1b[32m",
+    red:      "\x1b[31m",
+    yellow:   "\x1b[33m",
+    brown:    "\x1b[38;5;130m",
+    blue:     "\x1b[36m",
+    grey:     "\x1b[38;5;236m",
+    mgrey:    "\x1b[38;5;238m",
+    lgrey:    "\x1b[38;5;240m",
+    mlgrey:   "\x1b[38;5;242m",
+    llgrey:   "\x1b[38;5;244m",
+    lllgrey:  "\x1b[38;5;252m",
+    llllgrey: "\x1b[38;5;254m",
+    reset:    "\x1b[0m",
+};
+
+// For --palette colorblind: blue for good news and orange for bad instead
+// of green and red, and blue/orange/white golfer markers instead of
+// green/brown/cyan.
+
+pub const COLORBLIND_PALETTE: Palette = Palette {
+    green:    "\x1b[38;5;33m",
+    red:      "\x1b[38;5;208m",
+    yellow:   "\x1b[38;5;220m",
+    brown:    "\x1b[38;5;208m",
+    blue:     "\x1b[97m",
+    ..ANSI_PALETTE
+};
+
+// For --palette mono: the greys, bold, and underlining, but no hues.
+
+pub const MONO_PALETTE: Palette = Palette {
+    green:    "",
+    red:      "",
+    yellow:   "",
+    brown:    "",
+    blue:     "",
+    ..ANSI_PALETTE
+};
+
+pub const PLAIN_PALETTE: Palette = Palette {
+    bold:     "",
+    dim:      "",
+    uline:    "",
+    green:    "",
+    red:      "",
+    yellow:   "",
+    brown:    "",
+    blue:     "",
+    grey:     "",
+    mgrey:    "",
+    lgrey:    "",
+    mlgrey:   "",
+    llgrey:   "",
+    lllgrey:  "",
+    llllgrey: "",
+    reset:    "",
+};
+
+pub static PALETTE: std::sync::OnceLock<&Palette> = std::sync::OnceLock::new();
+
+pub fn palette() -> &'static Palette {
+    PALETTE.get().copied().unwrap_or(&ANSI_PALETTE)
+}
+
+// Every non-ASCII glyph the program draws (as opposed to ones that come
+// from the data), with plain ASCII stand-ins for --encode ascii. New glyphs
+// go here rather than straight into a format string.
+
+pub struct Glyphs {
+    pub bullet:     &'static str,
+    pub rule:       &'static str,
+    pub arrow:      &'static str,
+    pub times:      &'static str,
+    pub flag:       &'static str,
+    pub plus_minus: &'static str,
+    pub ellipsis:   &'static str,
+    pub win:        &'static str,
+    pub draw:       &'static str,
+    pub loss:       &'static str,
+    pub lead_flip:  &'static str,
+    pub eighths:    [&'static str; 8],
+    pub medals:     [&'static str; 4],  // Sole gold, then gold, silver, bronze.
+}
+
+const UTF8_GLYPHS: Glyphs = Glyphs {
+    bullet:     "—",
+    rule:       "—",
+    arrow:      "→",
+    times:      "×",
+    flag:       "⛳",
+    plus_minus: "±",
+    ellipsis:   "…",
+    win:        "✅",
+    draw:       "➖",
+    loss:       "❌",
+    lead_flip:  "⚡",
+    eighths:    ["▏", "▎", "▍", "▌", "▋", "▊", "▉", "█"],
+    medals:     ["💎", "🥇", "🥈", "🥉"],
+};
+
+pub const ASCII_GLYPHS: Glyphs = Glyphs {
+    bullet:     "-",
+    rule:       "-",
+    arrow:      ">",
+    times:      "x",
+    flag:       "golf",
+    plus_minus: "+/-",
+    ellipsis:   "...",
+    win:        "W",
+    draw:       "D",
+    loss:       "L",
+    lead_flip:  "!",
+    eighths:    ["|"; 8],
+    medals:     ["#1*", "#1", "#2", "#3"],
+};
+
+pub static GLYPHS: std::sync::OnceLock<&Glyphs> = std::sync::OnceLock::new();
+
+pub fn glyphs() -> &'static Glyphs {
+    GLYPHS.get().copied().unwrap_or(&UTF8_GLYPHS)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SummaryFormat {
+    Playful,
+    Plain,
+    Verbose,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Statusbar,
+    TotalsJson,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Text       => "txt",
+            Format::Statusbar  => "statusbar.txt",
+            Format::TotalsJson => "totals.json",
+            Format::Json       => "json",
+            Format::Csv        => "csv",
+            Format::Markdown   => "md",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Totals {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub delta: isize,
+    pub total: usize,
+    pub me_score_total: f32,
+    pub them_score_total: f32,
+    pub cutoff: String,
+    pub lang: String,
+    pub scoring: String,
+}
+
+// The whole comparison, hole by hole, for --format json. A golfer's entry
+// is their solution as it stood at the cutoff, or null if they had none.
+// Ranks start at 1 (a sole gold is rank 1 too, and says so in sole_gold).
+
+#[derive(Serialize)]
+pub struct Report<'a> {
+    pub holes: Vec<HoleReport<'a>>,
+    pub summary: Totals,
+}
+
+#[derive(Serialize)]
+pub struct HoleReport<'a> {
+    pub hole: &'a str,
+    pub name: &'a str,
+    pub url: &'a str,
+    pub links: &'a [HoleLink],
+    pub gold_length: usize,
+    pub delta: Option<isize>,
+    pub sole_gold: Option<&'a str>,     // Whichever of us holds it, if either.
+    pub me: Option<Solution>,
+    pub them: Option<Solution>,
+}
+
+// A solution as it goes in machine-readable output, where rank 0 (our
+// marker for a sole gold) would just look like a bug.
+
+fn public_rank(sol: &Solution) -> Solution {
+    Solution {rank: sol.rank.max(1), ..sol.clone()}
+}
+
+// Walk through how process_log got from a hole's raw solution log to the
+// ranks and scores in the report, for the golfers being compared.
+
+pub fn render_explanation(unprocessed: &SolutionLog, processed: &SolutionLog, golfers: &[String], scoring: &str, cutoff: &str, cutoff_at: DateTime<Utc>) -> String {
+    let Palette {bold, green, lgrey, mlgrey, llgrey, lllgrey, reset, ..} = palette();
+
+    let length = |sol: &Solution| if scoring == "chars" {sol.chars} else {sol.bytes};
+    let mut out = String::new();
+
+    let boundary = match date_format(cutoff) {
+        Some(IncludeEnd) => format!("submitted through {cutoff}"),
+        _                => format!("submitted before {cutoff}"),
+    };
+
+    out += &format!("{bold}How {} was scored{reset} {lgrey}({scoring}, {boundary}){reset}\n\n", sanitize(processed.label()));
+
+    // Each golfer's submissions, and which one was kept as their entry.
+
+    for golfer in golfers {
+        let mut submissions: Vec<&Solution> = unprocessed.solutions.iter()
+            .filter(|sol| sol.golfer == *golfer && sol.scoring == scoring && sol.submitted_at.is_none_or(|at| at < cutoff_at))
+            .collect();
+
+        submissions.sort_by_key(|sol| sol.submitted_at);
+
+        out += &format!("{lllgrey}Submissions by {}:{reset}\n", sanitize(golfer));
+
+        if submissions.is_empty() {
+            out += &format!("    {mlgrey}none{reset}\n\n");
+            continue;
+        }
+
+        let kept = processed.leaderboard.iter().find(|sol| sol.golfer == *golfer);
+        let ties = submissions.iter().filter(|sol| Some(length(sol)) == kept.map(|kept| kept.length)).count();
+
+        for sol in &submissions {
+            let is_kept = kept.is_some_and(|kept| kept.submitted == sol.submitted && kept.length == length(sol));
+
+            out += &format!(
+                "  {}{} {mlgrey}{}{reset}  {:>5}{}\n",
+                if is_kept {green} else {lgrey},
+                if is_kept {glyphs().arrow} else {" "},
+                sol.submitted,
+                length(sol),
+                if !is_kept {
+                    reset.to_string()
+                } else if ties > 1 {
+                    format!("  kept: shortest, and the earliest of {ties} at this length{reset}")
+                } else {
+                    format!("  kept: shortest{reset}")
+                },
+            );
+        }
+
+        out += "\n";
+    }
+
+    // The recreated leaderboard, around the golfers being compared.
+
+    out += &format!("{lllgrey}Leaderboard{reset} {lgrey}(shortest first, ties broken by earliest submission; {} entries){reset}\n", processed.leaderboard.len());
+
+    let gold = processed.gold_length;
+
+    for (i, sol) in processed.leaderboard.iter().enumerate() {
+        let near_golfer = processed.leaderboard.iter()
+            .enumerate()
+            .any(|(j, other)| golfers.contains(&other.golfer) && j.abs_diff(i) <= 1);
+
+        if i >= 3 && !near_golfer {
+            continue;
+        }
+
+        let note = if sol.rank == 0 {
+            "  rank 0: sole holder of the gold"
+        } else if i > 0 && sol.length == processed.leaderboard[i-1].length {
+            "  tied with the entry above, so same rank"
+        } else {
+            ""
+        };
+
+        out += &format!(
+            "  {}{:>4}  {:<20} {:>5}  {mlgrey}{gold} / {} {} 1000 = {:.1}{note}{reset}\n",
+            if golfers.contains(&sol.golfer) {green} else {llgrey},
+            sol.rank,
+            sanitize(&sol.golfer),
+            sol.length,
+            sol.length,
+            glyphs().times,
+            sol.score,
+        );
+    }
+
+    out += "\n";
+    out
+}
+
+// Show every solution two golfers submitted to a hole (up to the cutoff) in
+// two side-by-side columns, to see how the back-and-forth played out.
+
+pub fn render_history(log: &SolutionLog, golfers: &[String], scoring: &str, cutoff_at: DateTime<Utc>, width: usize) -> String {
+    let Palette {bold, green, brown, lgrey, llllgrey, reset, ..} = palette();
+
+    let columns: Vec<Vec<&Solution>> = golfers.iter().map(|golfer| {
+        let mut column: Vec<&Solution> = log.solutions.iter()
+            .filter(|sol| sol.golfer == *golfer && sol.scoring == scoring && sol.submitted_at.is_none_or(|at| at < cutoff_at))
+            .collect();
+
+        column.sort_by_key(|sol| sol.submitted_at);
+        column
+    }).collect();
+
+    let length = |sol: &Solution| if scoring == "chars" {sol.chars} else {sol.bytes};
+    let empty = "";
+
+    let mut out = format!("{llllgrey}{:^1$}{reset}\n\n", sanitize(log.label()), width * 2);
+
+    out += &format!(
+        "{bold}{green}{:^width$}{reset}{bold}{brown}{:^width$}{reset}\n",
+        sanitize(&golfers[0]), sanitize(&golfers[1]),
+    );
+
+    // Each entry is a 16-character date, two spaces, and a 5-character
+    // length, centered in its column.
+
+    let lpad = width.saturating_sub(23) / 2;
+    let rpad = width.saturating_sub(23).div_ceil(2);
+
+    for row in 0..columns[0].len().max(columns[1].len()) {
+        for (column, color) in columns.iter().zip([green, brown]) {
+            match column.get(row) {
+                Some(sol) => out += &format!(
+                    "{empty:lpad$}{lgrey}{:<16}{reset}  {color}{:>5}{reset}{empty:rpad$}",
+                    sol.submitted.get(..16).unwrap_or(&sol.submitted),
+                    length(sol),
+                ),
+                None => out += &format!("{empty:lpad$}{empty:23}{empty:rpad$}"),
+            }
+        }
+
+        out += "\n";
+    }
+
+    out += "\n";
+    out
+}
+
+// Walk every solution submitted to a hole (up to the cutoff) in date order,
+// and list each time anyone's best solution got shorter, so you can see who
+// chipped away at it when. The two golfers being compared are highlighted.
+
+pub fn render_timeline(log: &SolutionLog, golfers: &[String], scoring: &str, cutoff_at: DateTime<Utc>) -> String {
+    let Palette {bold, green, brown, yellow, mgrey, lgrey, llgrey, llllgrey, reset, ..} = palette();
+
+    let mut submissions: Vec<&Solution> = log.solutions.iter()
+        .filter(|sol| sol.scoring == scoring && sol.submitted_at.is_some_and(|at| at < cutoff_at))
+        .collect();
+
+    submissions.sort_by_key(|sol| sol.submitted_at);
+
+    let length = |sol: &Solution| if scoring == "chars" {sol.chars} else {sol.bytes};
+    let name_width = submissions.iter().map(|sol| sanitize(&sol.golfer).width()).max().unwrap_or(0);
+
+    let mut out = format!("{llllgrey}{}{reset}\n\n", sanitize(log.label()));
+    let mut bests: HashMap<&str, usize> = HashMap::new();
+    let mut gold = usize::MAX;
+
+    for sol in submissions {
+        let best = bests.get(sol.golfer.as_str()).copied().unwrap_or(usize::MAX);
+
+        if length(sol) >= best {
+            continue;
+        }
+
+        let color = match golfers.iter().position(|golfer| *golfer == sol.golfer) {
+            Some(0) => format!("{bold}{green}"),
+            Some(1) => format!("{bold}{brown}"),
+            _       => llgrey.to_string(),
+        };
+
+        let name = sanitize(&sol.golfer);
+        let change = if best == usize::MAX {"first".to_string()} else {format!("-{}", best - length(sol))};
+        let gold_note = if length(sol) < gold {format!("  {yellow}new gold{reset}")} else {String::new()};
+
+        out += &format!(
+            "{lgrey}{:<16}{reset}  {color}{name}{reset}{:padding$}  {:>5}  {mgrey}({change}){reset}{gold_note}\n",
+            sol.submitted.get(..16).unwrap_or(&sol.submitted),
+            "",
+            length(sol),
+            padding = name_width - name.width(),
+        );
+
+        bests.insert(&sol.golfer, length(sol));
+        gold = gold.min(length(sol));
+    }
+
+    out += "\n";
+    out
+}
+
+// Each hole's placings among several golfers, followed by a matrix of
+// everyone's head-to-head record (net wins of the row over the column).
+
+pub fn render_ranking(logs: &[SolutionLog], ranked: &[String], reverse: bool) -> String {
+    let Palette {green, red, mgrey, mlgrey, llgrey, llllgrey, reset, ..} = palette();
+
+    let place = |log: &SolutionLog, golfer: &str| {
+        let length = log.length_for(golfer);
+        1 + ranked.iter().filter(|other| log.length_for(other) < length).count()
+    };
+
+    let mut logs: Vec<&SolutionLog> = logs.iter().collect();
+    logs.sort_by_key(|log| (place(log, &ranked[0]), -(log.sort_score(&ranked[0]) as isize)));
+
+    if reverse {
+        logs.reverse();
+    }
+
+    let mut out = String::new();
+
+    for log in &logs {
+        let mut standings: Vec<(usize, &String)> = ranked.iter()
+            .filter(|golfer| log.length_for(golfer) < usize::MAX)
+            .map(|golfer| (log.length_for(golfer), golfer))
+            .collect();
+
+        standings.sort();
+
+        let standings: Vec<String> = standings.iter().map(|(length, golfer)| {
+            let color = golfer_color(log.golfers.iter().position(|other| &other == golfer).unwrap(), log.golfers.len());
+            format!("{mlgrey}{}.{reset} {color}{}{reset} {mgrey}{length}{reset}", place(log, golfer), sanitize(golfer))
+        }).collect();
+
+        let label = log.column_label();
+
+        out += &format!(
+            "{:padding$}{llllgrey}{label}{reset}  {}  {}\n",
+            "",
+            log.bar(),
+            standings.join("  "),
+            padding = log.hole_name_width.saturating_sub(label.width()),
+        );
+    }
+
+    let width = ranked.iter().map(|golfer| sanitize(golfer).chars().count()).max().unwrap_or(0).max(5);
+
+    out += "\n";
+    out += &format!("{llgrey}  {:width$}", "");
+
+    for golfer in ranked {
+        out += &format!("  {:>width$}", sanitize(golfer));
+    }
+
+    out += &format!("{reset}\n");
+
+    for row in ranked {
+        out += &format!("  {llllgrey}{:<width$}{reset}", sanitize(row));
+
+        for column in ranked {
+            let cell = if row == column {
+                format!("{mgrey}{:>width$}{reset}", "-")
+            } else {
+                let both: Vec<&&SolutionLog> = logs.iter().filter(|log| log.length_for(row) < usize::MAX && log.length_for(column) < usize::MAX).collect();
+                let net = both.iter().filter(|log| log.length_for(row) < log.length_for(column)).count() as isize
+                        - both.iter().filter(|log| log.length_for(row) > log.length_for(column)).count() as isize;
+                let color = match net {1.. => green, 0 => mlgrey, ..0 => red};
+                format!("{color}{:>width$}{reset}", if net > 0 {format!("+{net}")} else {net.to_string()})
+            };
+
+            out += &format!("  {cell}");
+        }
+
+        out += "\n";
+    }
+
+    out += "\n";
+    out
+}
+
+// A color for each golfer's marker. Up to three golfers get the usual
+// fixed colors; past that, colors are spread evenly around the hue wheel.
+
+fn golfer_color(index: usize, count: usize) -> String {
+    let Palette {green, brown, blue, ..} = palette();
+
+    if count <= 3 {
+        return [green, brown, blue][index].to_string();
+    }
+
+    if palette().green.is_empty() {
+        return String::new();
+    }
+
+    // Pick the nearest color in the terminal's 6x6x6 color cube to a fully
+    // saturated hue.
+
+    let hue = index as f32 / count as f32 * 6.0;
+    let fall = 1.0 - (hue % 2.0 - 1.0).abs();
+
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, fall, 0.0),
+        1 => (fall, 1.0, 0.0),
+        2 => (0.0, 1.0, fall),
+        3 => (0.0, fall, 1.0),
+        4 => (fall, 0.0, 1.0),
+        _ => (1.0, 0.0, fall),
+    };
+
+    let level = |channel: f32| (channel * 5.0).round() as usize;
+
+    format!("\x1b[38;5;{}m", 16 + 36 * level(r) + 6 * level(g) + level(b))
+}
+
+// My record against each of several opponents, best record first.
+
+pub fn render_league(logs: &[SolutionLog], me: &str, opponents: &[String], all_holes: bool) -> String {
+    let Palette {green, red, mlgrey, llgrey, lllgrey, llllgrey, reset, ..} = palette();
+
+    let mut rows: Vec<(&String, usize, usize, usize)> = opponents.iter().map(|them| {
+        let compared: Vec<&SolutionLog> = logs.iter().filter(|log|
+            if all_holes {
+                log.length_for(me) < usize::MAX || log.length_for(them) < usize::MAX
+            } else {
+                log.length_for(me) < usize::MAX && log.length_for(them) < usize::MAX
+            }
+        ).collect();
+
+        (
+            them,
+            compared.iter().filter(|log| log.length_for(me) <  log.length_for(them)).count(),
+            compared.iter().filter(|log| log.length_for(me) == log.length_for(them)).count(),
+            compared.iter().filter(|log| log.length_for(me) >  log.length_for(them)).count(),
+        )
+    }).collect();
+
+    rows.sort_by_key(|(_, wins, _, losses)| *losses as isize - *wins as isize);
+
+    let width = rows.iter().map(|(them, ..)| sanitize(them).chars().count()).max().unwrap_or(0).max("opponent".len());
+    let mut out = format!("{llgrey}  {:<width$}  {:>4} {:>4} {:>4}  {:>5}  {:>5}{reset}\n", "opponent", "W", "D", "L", "net", "holes");
+
+    for (them, wins, draws, losses) in rows {
+        let net = wins as isize - losses as isize;
+        let net_color = match net {1.. => green, 0 => mlgrey, ..0 => red};
+
+        out += &format!(
+            "  {llllgrey}{:<width$}{reset}  {green}{wins:>4}{reset} {lllgrey}{draws:>4}{reset} {red}{losses:>4}{reset}  {net_color}{:>5}{reset}  {mlgrey}{:>5}{reset}\n",
+            sanitize(them),
+            if net > 0 {format!("+{net}")} else {net.to_string()},
+            wins + draws + losses,
+        );
+    }
+
+    out += "\n";
+    out
+}
+
+// What to say instead of an all-zeros report when no holes are left to
+// compare: `solved` is how many holes each of us has solved, `shared` how
+// many there were to compare before `filters` (the flags that narrow the
+// list down further) had their way.
+
+pub fn render_no_holes(golfers: &[String], lang: &str, solved: (usize, usize), shared: usize, filters: &[&str]) -> String {
+    let (me, them) = (sanitize(&golfers[0]), sanitize(&golfers[1]));
+    let lang = if lang == "all" {"any language"} else {lang};
+    let holes = |count: usize| format!("{count} hole{}", if count == 1 {""} else {"s"});
+
+    if solved == (0, 0) {
+        format!("Neither {me} nor {them} has submitted anything in {lang}.\n")
+    } else if shared == 0 {
+        format!(
+            "No holes where both {me} and {them} have submitted in {lang}.\n{me} has solved {} on their own, {them} {}.\n",
+            holes(solved.0), solved.1,
+        )
+    } else {
+        format!("There {} {} to compare in {lang}, but {} left none of them.\n", if shared == 1 {"was"} else {"were"}, holes(shared), filters.join(" and "))
+    }
+}
+
+// List the holes <them> has a solution for and <me> doesn't, with the
+// shortest of their solutions (the ones likely easiest to catch) first.
+
+pub fn render_opportunities(logs: &[SolutionLog], golfers: &[String]) -> String {
+    let Palette {red, mgrey, llllgrey, reset, ..} = palette();
+
+    let mut out = String::new();
+    let (me, them) = (&golfers[0], &golfers[1]);
+
+    let mut open: Vec<&SolutionLog> = logs.iter()
+        .filter(|log| log.length_for(me) == usize::MAX && log.length_for(them) < usize::MAX)
+        .collect();
+
+    open.sort_by_key(|log| log.length_for(them));
+
+    for log in &open {
+        let label = log.column_label();
+        let length = log.length_for(them);
+
+        out += &format!(
+            "{:padding$}{llllgrey}{label}{reset}  {}  {red}{length} {}{}{reset} {mgrey}(gold {}){reset}\n",
+            "",
+            log.bar(),
+            unit(&log.scoring), if length == 1 {""} else {"s"},
+            log.gold_length,
+            padding = log.hole_name_width.saturating_sub(label.width()),
+        );
+    }
+
+    out += &format!(
+        "\n{} hole{} {} has solved that {} hasn't\n\n",
+        open.len(), if open.len() == 1 {""} else {"s"},
+        sanitize(them), sanitize(me),
+    );
+
+    out
+}
+
+// List the holes where the winner between <me> and <them> depends on which
+// of the two languages you look at.
+
+pub fn render_flips(golfers: &[String], (lang_a, logs_a): (&str, &[SolutionLog]), (lang_b, logs_b): (&str, &[SolutionLog]), hole_name_width: usize) -> String {
+    let Palette {green, red, mgrey, mlgrey, llgrey, llllgrey, reset, ..} = palette();
+
+    let mut out = String::new();
+    let (me, them) = (&golfers[0], &golfers[1]);
+
+    let standing = |log: &SolutionLog| {
+        let (len_0, len_1) = (log.length_for(me), log.length_for(them));
+
+        if len_0 == usize::MAX || len_1 == usize::MAX {
+            return None;
+        }
+
+        let winner = match len_0.cmp(&len_1) {
+            Ordering::Less    => format!("{green}{}{reset}", sanitize(me)),
+            Ordering::Greater => format!("{red}{}{reset}", sanitize(them)),
+            Ordering::Equal   => format!("{mlgrey}tie{reset}"),
+        };
+
+        Some((len_0.cmp(&len_1), format!("{winner} {mgrey}({len_0}-{len_1}){reset}")))
+    };
+
+    let mut flips = 0;
+
+    for (log_a, log_b) in logs_a.iter().zip(logs_b) {
+        let (Some((order_a, standing_a)), Some((order_b, standing_b))) = (standing(log_a), standing(log_b)) else {
+            continue;
+        };
+
+        if order_a != order_b {
+            flips += 1;
+            let label = log_a.column_label();
+            out += &format!(
+                "{:padding$}{llllgrey}{label}{reset}  {llgrey}{lang_a}:{reset} {standing_a}  {llgrey}{lang_b}:{reset} {standing_b}\n",
+                "",
+                padding = hole_name_width.saturating_sub(label.width()),
+            );
+        }
+    }
+
+    out += &format!("\n{flips} hole{} where {lang_a} vs. {lang_b} decides who wins\n\n", if flips == 1 {""} else {"s"});
+    out
+}
+
+// List the holes where something changed between two cutoffs: whatever
+// <me> and <them> took off their solutions in between, and whether the
+// lead changed hands.
+
+pub fn render_changes(golfers: &[String], (since, logs_a): (&str, &[SolutionLog]), (until, logs_b): (&str, &[SolutionLog]), hole_name_width: usize) -> String {
+    let Palette {green, red, mgrey, mlgrey, llgrey, llllgrey, reset, ..} = palette();
+    let Glyphs {arrow, ..} = glyphs();
+
+    let mut out = String::new();
+    let (me, them) = (&golfers[0], &golfers[1]);
+
+    // A solution can only get shorter (or appear) as the cutoff moves
+    // later, so any change is an improvement.
+
+    let change = |golfer: &String, color: &str, log_a: &SolutionLog, log_b: &SolutionLog| {
+        let (then, now) = (log_a.length_for(golfer), log_b.length_for(golfer));
+
+        let lengths = match (then, now) {
+            (_, usize::MAX)   => format!("{mgrey}unsolved{reset}"),
+            _ if then == now  => format!("{mgrey}{now}{reset}"),
+            (usize::MAX, _)   => format!("{color}new {now}{reset}"),
+            _                 => format!("{then} {arrow} {now} {color}(-{}){reset}", then - now),
+        };
+
+        (then != now, format!("{}: {lengths}", sanitize(golfer)))
+    };
+
+    let leader = |log: &SolutionLog| {
+        let (len_0, len_1) = (log.length_for(me), log.length_for(them));
+
+        if len_0 == usize::MAX || len_1 == usize::MAX {
+            return None;
+        }
+
+        Some(match len_0.cmp(&len_1) {
+            Ordering::Less    => format!("{green}{}{reset}", sanitize(me)),
+            Ordering::Greater => format!("{red}{}{reset}", sanitize(them)),
+            Ordering::Equal   => format!("{mlgrey}tie{reset}"),
+        })
+    };
+
+    let (mut changed, mut flips) = (0, 0);
+
+    for (log_a, log_b) in logs_a.iter().zip(logs_b) {
+        let (mine_changed, mine)     = change(me,   green, log_a, log_b);
+        let (theirs_changed, theirs) = change(them, red,   log_a, log_b);
+
+        if !mine_changed && !theirs_changed {
+            continue;
+        }
+
+        changed += 1;
+
+        let lead = match (leader(log_a), leader(log_b)) {
+            (Some(then), Some(now)) if then != now => {
+                flips += 1;
+                format!("  {llgrey}lead:{reset} {then} {arrow} {now}")
+            }
+            (None, Some(now)) => format!("  {llgrey}lead:{reset} {now}"),
+            _ => String::new(),
+        };
+
+        let label = log_b.column_label();
+
+        out += &format!(
+            "{:padding$}{llllgrey}{label}{reset}  {mine}  {theirs}{lead}\n",
+            "",
+            padding = hole_name_width.saturating_sub(label.width()),
+        );
+    }
+
+    out += &format!(
+        "\n{changed} hole{} changed between {since} and {until}, {flips} of them with the lead changing hands\n\n",
+        if changed == 1 {""} else {"s"},
+    );
+
+    out
+}
+
+// Everything the renderers need to know about a finished comparison.
+
+pub struct Comparison {
+    pub logs: Vec<SolutionLog>,
+    pub golfers: Vec<String>,
+    pub lang: String,
+    pub scoring: String,
+    pub scoring_mode: String,           // What --scoring asked for.
+    pub cutoff: String,
+    pub historical: bool,
+    pub compare_to_self_past: bool,
+    pub near: Option<usize>,
+    pub unattempted: Option<usize>,     // Set by --stats.
+    pub mini_board: bool,
+    pub links: bool,
+    pub group_by_category: bool,
+    pub summary_format: SummaryFormat,
+    pub only: Option<Ordering>,         // Set by --only-wins and friends.
+    pub min_delta: usize,
+    pub limit: Option<usize>,
+    pub hole_name_width: usize,
+    pub bar_width: usize,
+}
+
+impl Comparison {
+    // The holes that get listed, which with --only-wins (etc.), --min-delta
+    // or --limit can be fewer than the ones that count towards the totals.
+
+    pub fn shown(&self) -> impl Iterator<Item = &SolutionLog> {
+        let (me, them) = (&self.golfers[0], &self.golfers[1]);
+
+        self.logs.iter().filter(move |log|
+            self.only.is_none_or(|only| log.length_for(me).cmp(&log.length_for(them)) == only) &&
+            log.length_for(me).abs_diff(log.length_for(them)) >= self.min_delta
+        ).take(self.limit.unwrap_or(usize::MAX))
+    }
+
+    pub fn render(&self, format: Format) -> Result<String, Box<dyn Error>> {
+        Ok(match format {
+            Format::Text       => self.render_text()?,
+            Format::Statusbar  => self.render_statusbar()?,
+            Format::TotalsJson => self.render_totals_json()?,
+            Format::Json       => self.render_json()?,
+            Format::Csv        => self.render_csv(),
+            Format::Markdown   => self.render_markdown(),
+        })
+    }
+
+    pub fn render_text(&self) -> Result<String, fmt::Error> {
+        let Palette {bold, uline, green, red, mgrey, lgrey, mlgrey, llgrey, lllgrey, reset, ..} = palette();
+
+        use fmt::Write;
+
+        let mut out = String::new();
+        let golfers = &self.golfers;
+        let (wins, draws, losses) = self.wdl();
+        let total = wins + draws + losses;
+        let hole_name_width = self.hole_name_width;
+        let bar_width = self.bar_width;
+        let cutoff = &self.cutoff;
+
+        // Compute more stuff for formatting.
+
+        let num_len = |num: usize| if num > 0 {num.ilog(10) + 1} else {1};
+        let wdl_width = (num_len(wins) + num_len(draws) + num_len(losses) + 6) as usize;
+
+        let asterisk = if self.historical {"*"} else {""};
+
+        let empty  = "";
+        let asof   = "as of";
+        // These all saturate at zero, so that a long language name or
+        // cutoff (or a narrow --hole-name-width or --score-bar-width) just
+        // makes for lopsided output rather than a crash.
+
+        let indent = hole_name_width.saturating_sub(self.lang.len() + 1 + asof.chars().count() + 1 + cutoff.len() + asterisk.len());
+        let lcenter = bar_width.saturating_sub(wdl_width) / 2;
+        let rcenter = bar_width.saturating_sub(wdl_width).div_ceil(2);
+
+        let names_v1 = format!("{} vs. {}", sanitize(&golfers[0]), sanitize(&golfers[1]));
+        let names_v2 = format!("{} v. {}", sanitize(&golfers[0]), sanitize(&golfers[1]));
+
+        let names = if names_v1.len().abs_diff(wdl_width).is_multiple_of(2) {
+            names_v1
+        } else {
+            names_v2
+        };
+
+        let names_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(names.len()) / 2;
+
+        // Print the holes (with --group-by-category, under a header for
+        // each category, followed by that category's own W/D/L).
+
+        let mut logs: Vec<&SolutionLog> = self.shown().collect();
+
+        if self.group_by_category {
+            logs.sort_by(|a, b| a.category.cmp(&b.category));
+        }
+
+        for (i, log) in logs.iter().enumerate() {
+            let first_in_category = i == 0 || logs[i-1].category != log.category;
+            let last_in_category  = i == logs.len() - 1 || logs[i+1].category != log.category;
+
+            if self.group_by_category && first_in_category {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+
+                writeln!(out, "{empty:0$}{bold}{llgrey}{1}{reset}", hole_name_width + 2, sanitize(&log.category))?;
+            }
+
+            writeln!(out, "{log}")?;
+
+            if self.mini_board {
+                writeln!(out, "{empty:0$}{1}", hole_name_width + 2, log.mini_board(&golfers[0]))?;
+            }
+
+            if self.links {
+                for link in &log.links {
+                    writeln!(out, "{empty:0$}{mgrey}{1}: {2}{reset}", hole_name_width + 2, sanitize(&link.name), hyperlink(&sanitize(&link.url), &link.url))?;
+                }
+            }
+
+            if self.group_by_category && last_in_category {
+                let category: Vec<&&SolutionLog> = logs.iter().filter(|other| other.category == log.category).collect();
+                let count = |ordering| category.iter().filter(|log| log.length_for(&golfers[0]).cmp(&log.length_for(&golfers[1])) == ordering).count();
+
+                writeln!(
+                    out, "{empty:0$}{green}{1}{reset} {lgrey}/{reset} {lllgrey}{2}{reset} {lgrey}/{reset} {red}{3}{reset}",
+                    hole_name_width + 2,
+                    count(Ordering::Less),
+                    count(Ordering::Equal),
+                    count(Ordering::Greater),
+                )?;
+            }
+        }
+
+        // Print the after-summary.
+
+        writeln!(out)?;
+        write!(out, "{empty:indent$}{uline}{llgrey}{}{reset} {lgrey}{asof}{reset} {llgrey}{uline}{}{reset}{llgrey}{asterisk}{reset}  ", self.lang, cutoff)?;
+        write!(out, "{empty:lcenter$}{green}{wins}{reset} {lgrey}/{reset} {lllgrey}{draws}{reset} {lgrey}/{reset} {red}{losses}{reset}{empty:rcenter$}  ")?;
+
+        write!(out, "{}", self.headline())?;
+        if logs.len() < total {
+            write!(out, " {mlgrey}({total} holes, {} shown){reset}", logs.len())?;
+        } else {
+            write!(out, " {mlgrey}({total} holes){reset}")?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{empty:names_indent$}{llgrey}{names}{reset}")?;
+
+        // How far ahead I am in total, over the holes we've both solved.
+        // (When comparing against my past self, the "Saved" line below
+        // already says this.)
+
+        if !self.compare_to_self_past {
+            let ahead: isize = self.logs.iter()
+                .filter(|log| log.length_for(&golfers[0]) < usize::MAX && log.length_for(&golfers[1]) < usize::MAX)
+                .map(|log| log.length_for(&golfers[1]) as isize - log.length_for(&golfers[0]) as isize)
+                .sum();
+
+            let units = format!("{}{}", unit(&self.scoring), if ahead.abs() == 1 {""} else {"s"});
+
+            let (color, total_line) = match ahead {
+                1.. => (green, format!("{} is {ahead} {units} ahead overall", sanitize(&golfers[0]))),
+                ..0 => (red,   format!("{} is {} {units} behind overall", sanitize(&golfers[0]), -ahead)),
+                 0  => (mlgrey, format!("Even on total {units}")),
+            };
+
+            let total_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(total_line.width()) / 2;
+
+            writeln!(out, "{empty:total_indent$}{color}{total_line}{reset}")?;
+        }
+
+        // With --scoring points, also give the points each of us has from
+        // these holes.
+
+        if self.logs.first().is_some_and(|log| log.points) {
+            let total = |golfer: &str| self.logs.iter().map(|log| log.points_for(golfer)).sum::<usize>();
+            let (points_0, points_1) = (total(&golfers[0]), total(&golfers[1]));
+
+            let points_line = format!("Points: {points_0} vs. {points_1}");
+            let points_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(points_line.len()) / 2;
+
+            writeln!(out, "{empty:points_indent$}{mlgrey}{points_line}{reset}")?;
+        }
+
+        // With --scoring both, also give the W/D/L going by chars.
+
+        let paired: Vec<&SolutionLog> = self.logs.iter().filter_map(|log| log.paired.as_deref()).collect();
+
+        if !paired.is_empty() {
+            let count = |ordering| paired.iter().filter(|log| log.length_for(&golfers[0]).cmp(&log.length_for(&golfers[1])) == ordering).count();
+            let (wins, draws, losses) = (count(Ordering::Less), count(Ordering::Equal), count(Ordering::Greater));
+
+            let chars_line = format!("By chars: {wins} / {draws} / {losses}");
+            let chars_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(chars_line.len()) / 2;
+
+            writeln!(
+                out, "{empty:chars_indent$}{mlgrey}By chars:{reset} {green}{wins}{reset} {lgrey}/{reset} {lllgrey}{draws}{reset} {lgrey}/{reset} {red}{losses}{reset}",
+            )?;
+        }
+
+        // A gold shared by the two of us counts for both, and gets called
+        // out so that the two numbers don't look like they add up wrong.
+
+        let holds_gold = |golfer: &str, log: &&SolutionLog| log.length_for(golfer) == log.gold_length;
+
+        let golds_0 = self.logs.iter().filter(|log| holds_gold(&golfers[0], log)).count();
+        let golds_1 = self.logs.iter().filter(|log| holds_gold(&golfers[1], log)).count();
+        let shared  = self.logs.iter().filter(|log| holds_gold(&golfers[0], log) && holds_gold(&golfers[1], log)).count();
+
+        let gold_line = format!(
+            "{} holds gold on {golds_0} hole{}, {} on {golds_1}{}",
+            sanitize(&golfers[0]), if golds_0 == 1 {""} else {"s"},
+            sanitize(&golfers[1]),
+            if shared > 0 {format!(" ({shared} shared)")} else {String::new()},
+        );
+
+        let gold_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(gold_line.width()) / 2;
+
+        writeln!(out, "{empty:gold_indent$}{mlgrey}{gold_line}{reset}")?;
+
+        if self.compare_to_self_past {
+            let saved: isize = self.logs.iter()
+                .filter(|log| log.length_for(&golfers[0]) < usize::MAX && log.length_for(&golfers[1]) < usize::MAX)
+                .map(|log| log.length_for(&golfers[1]) as isize - log.length_for(&golfers[0]) as isize)
+                .sum();
+
+            let saved_line = format!("Saved {saved} {} across {wins} hole{}", self.scoring, if wins == 1 {""} else {"s"});
+            let saved_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(saved_line.len()) / 2;
+
+            writeln!(out, "{empty:saved_indent$}{bold}{green}{saved_line}{reset}")?;
+        }
+
+        if let Some(near) = self.near {
+            let deltas: Vec<isize> = self.logs.iter()
+                .filter(|log| log.length_for(&golfers[0]) < usize::MAX && log.length_for(&golfers[1]) < usize::MAX)
+                .map(|log| log.length_for(&golfers[0]) as isize - log.length_for(&golfers[1]) as isize)
+                .filter(|delta| *delta != 0 && delta.unsigned_abs() <= near)
+                .collect();
+
+            let ahead  = deltas.iter().filter(|delta| **delta < 0).count();
+            let behind = deltas.iter().filter(|delta| **delta > 0).count();
+
+            let near_line = format!(
+                "Within {near} {}{}: {ahead} ahead, {behind} behind",
+                unit(&self.scoring), if near == 1 {""} else {"s"},
+            );
+
+            let near_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(near_line.chars().count()) / 2;
+
+            writeln!(out, "{empty:near_indent$}{mlgrey}{near_line}{reset}")?;
+        }
+
+        if let Some(unattempted) = self.unattempted {
+            let mut deltas: Vec<isize> = self.logs.iter()
+                .filter(|log| log.length_for(&golfers[0]) < usize::MAX && log.length_for(&golfers[1]) < usize::MAX)
+                .map(|log| log.length_for(&golfers[0]) as isize - log.length_for(&golfers[1]) as isize)
+                .collect();
+
+            deltas.sort();
+
+            let count = deltas.len();
+
+            let (mean, median) = match count {
+                0 => (0.0, 0.0),
+                _ => (
+                    deltas.iter().sum::<isize>() as f32 / count as f32,
+                    (deltas[(count - 1) / 2] + deltas[count / 2]) as f32 / 2.0,
+                ),
+            };
+
+            let stats_line = format!(
+                "Delta per hole: {mean:+.1} {unit}s on average, {median:+.1} median; {unattempted} hole{} neither has solved",
+                if unattempted == 1 {""} else {"s"},
+                unit = unit(&self.scoring),
+            );
+
+            let stats_indent = (hole_name_width * 2 + 4 + bar_width).saturating_sub(stats_line.width()) / 2;
+
+            writeln!(out, "{empty:stats_indent$}{mlgrey}{stats_line}{reset}")?;
+        }
+
+        writeln!(out)?;
+        writeln!(out)?;
+
+        Ok(out)
+    }
+
+    // The bit of the summary line that says who's ahead.
+
+    pub fn headline(&self) -> String {
+        let Palette {bold, green, red, reset, ..} = palette();
+
+        let (wins, draws, losses) = self.wdl();
+        let delta = losses as isize - wins as isize;
+        let (me, them) = (sanitize(&self.golfers[0]), sanitize(&self.golfers[1]));
+
+        match self.summary_format {
+            SummaryFormat::Playful => match delta {
+                1..   => format!("{bold}{red}+{delta} loss{}{reset}", if delta.abs() > 1 {"es"} else {"!"}),
+                0     => "Tie!!".to_string(),
+                ..=-1 => format!("{bold}{green}+{} win{}!!!{reset}", -delta, if delta.abs() > 1 {"s!"} else {""}),
+            },
+
+            SummaryFormat::Plain => {
+                let leader = match delta {
+                    1..   => format!("{red}{them} leads by {delta}{reset}"),
+                    0     => "Tied".to_string(),
+                    ..=-1 => format!("{green}{me} leads by {}{reset}", -delta),
+                };
+
+                format!("{leader} (W{wins} D{draws} L{losses})")
+            },
+
+            SummaryFormat::Verbose => {
+                let score = |golfer: &str| self.logs.iter().map(|log| log.score_for(golfer)).sum::<f32>().round();
+
+                format!(
+                    "{me} won {wins}, drew {draws}, and lost {losses}; total score {me} {} vs. {them} {}",
+                    score(&self.golfers[0]), score(&self.golfers[1]),
+                )
+            },
+        }
+    }
+
+    pub fn render_statusbar(&self) -> Result<String, fmt::Error> {
+        let Palette {green, red, reset, ..} = palette();
+
+        let (wins, draws, losses) = self.wdl();
+        let delta = losses as isize - wins as isize;
+        let total = wins + draws + losses;
+
+        Ok(match delta {
+            1..   => format!("{} {red}-{delta}{reset} ({total})\n", glyphs().flag),
+            0     => format!("{} {}0 ({total})\n", glyphs().flag, glyphs().plus_minus),
+            ..=-1 => format!("{} {green}+{}{reset} ({total})\n", glyphs().flag, -delta),
+        })
+    }
+
+    pub fn render_totals_json(&self) -> Result<String, serde_json::Error> {
+        Ok(serde_json::to_string(&self.totals())? + "\n")
+    }
+
+    pub fn render_json(&self) -> Result<String, serde_json::Error> {
+        let (me, them) = (&self.golfers[0], &self.golfers[1]);
+
+        let holes = self.shown().map(|log| {
+            let mine   = log.solutions.iter().find(|sol| &sol.golfer == me);
+            let theirs = log.solutions.iter().find(|sol| &sol.golfer == them);
+
+            HoleReport {
+                hole: &log.hole_id,
+                name: &log.hole_name,
+                url: &log.url,
+                links: &log.links,
+                gold_length: log.gold_length,
+                delta: mine.zip(theirs).map(|(mine, theirs)| mine.length as isize - theirs.length as isize),
+                sole_gold: mine.into_iter().chain(theirs).find(|sol| sol.rank == 0).map(|sol| sol.golfer.as_str()),
+                me: mine.map(public_rank),
+                them: theirs.map(public_rank),
+            }
+        }).collect();
+
+        Ok(serde_json::to_string_pretty(&Report {holes, summary: self.totals()})? + "\n")
+    }
+
+    pub fn render_csv(&self) -> String {
+        let (me, them) = (&self.golfers[0], &self.golfers[1]);
+        let mut out = String::from("hole_id,hole_name,category,me_length,them_length,delta,gold_length,me_rank,them_rank,sole_gold\n");
+
+        for log in self.shown() {
+            let mine   = log.solutions.iter().find(|sol| &sol.golfer == me);
+            let theirs = log.solutions.iter().find(|sol| &sol.golfer == them);
+            let field  = |value: Option<String>| value.unwrap_or_default();
+
+            let row = [
+                log.hole_id.clone(),
+                log.hole_name.clone(),
+                log.category.clone(),
+                field(mine.map(|sol| sol.length.to_string())),
+                field(theirs.map(|sol| sol.length.to_string())),
+                field(mine.zip(theirs).map(|(mine, theirs)| (mine.length as isize - theirs.length as isize).to_string())),
+                log.gold_length.to_string(),
+                field(mine.map(|sol| sol.rank.max(1).to_string())),
+                field(theirs.map(|sol| sol.rank.max(1).to_string())),
+                field(mine.into_iter().chain(theirs).find(|sol| sol.rank == 0).map(|sol| sol.golfer.clone())),
+            ];
+
+            out += &row.iter().map(|value| csv_field(value)).collect::<Vec<_>>().join(",");
+            out += "\n";
+        }
+
+        out
+    }
+
+    // A GitHub-flavored Markdown table, with no colors, for pasting into
+    // issues and chat.
+
+    pub fn render_markdown(&self) -> String {
+        let (me, them) = (&self.golfers[0], &self.golfers[1]);
+        let cell = |text: &str| sanitize(text).replace('|', "\\|");
+        let show_len = |len: usize| if len == usize::MAX {"-".to_string()} else {len.to_string()};
+
+        let mut rows = vec![[
+            "Hole".to_string(),
+            cell(me),
+            cell(them),
+            "Delta".to_string(),
+            "".to_string(),
+        ]];
+
+        for log in self.shown() {
+            let (mine, theirs) = (log.length_for(me), log.length_for(them));
+
+            rows.push([
+                format!("[{}]({})", cell(log.label()), log.url),
+                show_len(mine),
+                show_len(theirs),
+                if mine == usize::MAX || theirs == usize::MAX {
+                    "-".to_string()
+                } else {
+                    format!("{:+}", mine as isize - theirs as isize)
+                },
+                match mine.cmp(&theirs) {
+                    Ordering::Less    => glyphs().win,
+                    Ordering::Equal   => glyphs().draw,
+                    Ordering::Greater => glyphs().loss,
+                }.to_string(),
+            ]);
+        }
+
+        let widths: Vec<usize> = (0..5).map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap()).collect();
+        let line = |row: &[String]| format!(
+            "| {} |\n",
+            row.iter().zip(&widths).enumerate().map(|(i, (text, &width))|
+                if i == 0 {format!("{text:<width$}")} else {format!("{text:>width$}")}
+            ).collect::<Vec<_>>().join(" | "),
+        );
+
+        let mut out = line(&rows[0]);
+
+        out += &format!(
+            "|{}|\n",
+            widths.iter().enumerate().map(|(i, &width)|
+                if i == 0 {format!(":{}", "-".repeat(width + 1))} else {format!("{}:", "-".repeat(width + 1))}
+            ).collect::<Vec<_>>().join("|"),
+        );
+
+        for row in &rows[1..] {
+            out += &line(row);
+        }
+
+        let (wins, draws, losses) = self.wdl();
+
+        out += &format!(
+            "\n**{} vs. {}** ({}, {} as of {}): {wins} / {draws} / {losses}\n",
+            cell(me), cell(them), self.lang, self.scoring_mode, self.cutoff,
+        );
+
+        out
+    }
+
+    pub fn totals(&self) -> Totals {
+        let (wins, draws, losses) = self.wdl();
+
+        Totals {
+            wins, draws, losses,
+            delta: losses as isize - wins as isize,
+            total: wins + draws + losses,
+            // Folded from 0.0, since an empty f32 sum() comes out as -0.0.
+
+            me_score_total:   self.logs.iter().map(|log| log.score_for(&self.golfers[0])).fold(0.0, |a, b| a + b),
+            them_score_total: self.logs.iter().map(|log| log.score_for(&self.golfers[1])).fold(0.0, |a, b| a + b),
+            cutoff: self.cutoff.clone(),
+            lang: self.lang.clone(),
+            scoring: self.scoring_mode.clone(),
+        }
+    }
+
+    pub fn wdl(&self) -> (usize, usize, usize) {
+        let (me, them) = (&self.golfers[0], &self.golfers[1]);
+
+        (
+            self.logs.iter().filter(|log| log.length_for(me) <  log.length_for(them)).count(),
+            self.logs.iter().filter(|log| log.length_for(me) == log.length_for(them)).count(),
+            self.logs.iter().filter(|log| log.length_for(me) >  log.length_for(them)).count(),
+        )
+    }
+}
+
+// Older Windows consoles print ANSI escapes literally unless virtual
+// terminal processing is switched on, so try to switch it on. If that
+// can't be done, we print without colors instead.
+
+#[cfg(windows)]
+pub fn enable_colors() -> bool {
+    enable_ansi_support::enable_ansi_support().is_ok()
+}
+
+#[cfg(not(windows))]
+pub fn enable_colors() -> bool {
+    true
+}
+
+// Make some text into a clickable link (in terminals that support OSC 8
+// hyperlinks; others just show the text). Like colors, links are left out
+// when the output isn't going to a terminal.
+
+fn hyperlink(text: &str, url: &str) -> String {
+    if palette().reset.is_empty() {
+        text.to_string()
+    } else {
+        format!("\x1b]8;;{}\x1b\\{text}\x1b]8;;\x1b\\", url.replace(|c: char| c.is_control(), ""))
+    }
+}
+
+// The unit a length is counted in under a scoring method ("byte" or
+// "char"), to go before an "s" as needed.
+
+fn unit(scoring: &str) -> &'static str {
+    match scoring {
+        "chars" => "char",
+        _       => "byte",
+    }
+}
+
+// Quote a CSV field if it needs it (hole names can contain commas).
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// A report line is the hole name column, the score bar, and then about as
+// much again as the name column for the delta and scores, so the default
+// 33/20 layout wants 90 columns. On a terminal of a different width, the
+// name column shrinks (but never below what's needed for most hole names)
+// and the bar gives or takes up the rest. When stdout isn't a terminal,
+// the defaults are kept as is.
+
+pub fn column_widths(hole_name_width: Option<usize>, score_bar_width: Option<usize>) -> (usize, usize) {
+    let terminal_width = std::io::stdout().is_terminal()
+        .then(|| terminal_size::terminal_size_of(std::io::stdout()))
+        .flatten()
+        .map(|(terminal_size::Width(width), _)| width as usize);
+
+    let Some(terminal_width) = terminal_width else {
+        return (hole_name_width.unwrap_or(33), score_bar_width.unwrap_or(20));
+    };
+
+    let hole_name_width = hole_name_width.unwrap_or_else(||
+        (terminal_width.saturating_sub(4 + score_bar_width.unwrap_or(20)) / 2).clamp(16, 33)
+    );
+
+    let score_bar_width = score_bar_width.unwrap_or_else(||
+        terminal_width.saturating_sub(4 + hole_name_width * 2).clamp(10, 60)
+    );
+
+    (hole_name_width, score_bar_width)
+}
+
+// Golfer logins and hole names come straight from the API (or the command
+// line) and get printed into colored terminal output, so escape any control
+// characters in them rather than letting them mess with the terminal.
+
+pub fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() {c.escape_default().to_string()} else {c.to_string()})
+        .collect()
+}
+
+// Columns are lined up by how wide things show up in the terminal, not by
+// how many chars they are, since a CJK character (say) takes up two columns.
+// Anything too wide for its column is cut short with an ellipsis.
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let ellipsis = glyphs().ellipsis;
+    let room = width.saturating_sub(ellipsis.width());
+    let mut used = 0;
+
+    let kept: String = text.chars().take_while(|c| {
+        used += c.width().unwrap_or(0);
+        used <= room
+    }).collect();
+
+    kept + ellipsis
+}
+
+impl fmt::Display for SolutionLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Palette {mgrey, llllgrey, reset, ..} = palette();
+
+        let label = self.column_label();
+        let padding = self.hole_name_width.saturating_sub(label.width());
+
+        write!(f, "{:padding$}{llllgrey}{}{reset}  ", "", hyperlink(&label, &self.url))?;
+        self.write_standing(f)?;
+
+        // With --scoring both, the chars standing goes on a line of its own
+        // right under the bytes one.
+
+        if let Some(paired) = &self.paired {
+            let padding = self.hole_name_width.saturating_sub(paired.scoring.width());
+            write!(f, "\n{:padding$}{mgrey}{}{reset}  ", "", paired.scoring)?;
+            paired.write_standing(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SolutionLog {
+    // Everything on a hole's line after the name: the bar, the delta, and
+    // the lengths.
+
+    pub fn write_standing(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Palette {dim, green, red, yellow, mgrey, mlgrey, reset, ..} = palette();
+
+        write!(f, "{}", self.bar())?;
+
+        let len_0 = self.length_for(&self.golfers[0]);
+        let len_1 = self.length_for(&self.golfers[1]);
+        let delta = len_0 as isize - len_1 as isize;
+
+        // With --all-holes, one of the two golfers may not have a solution
+        // at all, in which case there's no meaningful delta to show.
+
+        if len_1 == usize::MAX {
+            write!(f, "  {dim}{green}Unopposed{reset}")?;
+        } else if len_0 == usize::MAX {
+            write!(f, "  {dim}{red}Unsolved{reset}")?;
+        } else if self.points {
+            let delta = self.points_for(&self.golfers[0]) as isize - self.points_for(&self.golfers[1]) as isize;
+
+            match delta {
+                1.. => write!(f, "  {dim}{green}+{delta} point{}{reset}", if delta > 1 {"s"} else {""})?,
+                ..0 => write!(f, "  {dim}{red}{delta} point{}{reset}", if delta < -1 {"s"} else {""})?,
+                 0  => write!(f, "  {mlgrey}Tie{}{reset}", if len_0 == self.gold_length {"d gold"} else {""})?,
+            };
+        } else {
+            match delta {
+                ..0 => write!(f, "  {dim}{green}{delta} {}{}{reset}", unit(&self.scoring), if delta.abs() > 1 {"s"} else {""})?,
+                1.. => write!(f, "  {dim}{red}+{delta} {}{}{reset}",  unit(&self.scoring), if delta.abs() > 1 {"s"} else {""})?,
+                 0  => write!(f, "  {mlgrey}Tie{}{reset}", if len_0 == self.gold_length {"d gold"} else {""})?,
+            };
+
+            // A zero-length solution would make for a division by zero, so
+            // just leave the percentage off in that (hypothetical) case.
+
+            let shorter = len_0.min(len_1);
+
+            if self.delta_percent && delta != 0 && shorter > 0 {
+                let percent = (delta.unsigned_abs() as f32 / shorter as f32 * 100.0).round();
+                write!(f, " {dim}{}({percent}%){reset}", if delta < 0 {green} else {red})?;
+            }
+        }
+
+        // A missing side says so in words, since "(93--|93)" would just
+        // look like a negative number or a typo.
+
+        let show_len = |len: usize| if len == usize::MAX {"none".to_string()} else {len.to_string()};
+
+        write!(
+            f, " {mgrey}({}-{}|{}){reset}",
+            show_len(len_0),
+            show_len(len_1),
+            self.gold_length,
+        )?;
+
+        if self.medals {
+            let medal_for = |golfer: &str| self.solutions.iter()
+                .find(|sol| sol.golfer == golfer)
+                .and_then(|sol| glyphs().medals.get(sol.rank))
+                .map_or(format!("{mgrey}-{reset}"), |medal| medal.to_string());
+
+            write!(f, "  {} {}", medal_for(&self.golfers[0]), medal_for(&self.golfers[1]))?;
+        }
+
+        // A sole gold is rank 0 internally, but it's still first place.
+
+        if self.field_rank {
+            let place_of = |golfer: &str| self.solutions.iter()
+                .find(|sol| sol.golfer == golfer)
+                .map_or("-".to_string(), |sol| format!("#{}", sol.rank.max(1)));
+
+            write!(
+                f, "  {mlgrey}{} vs. {} of {}{reset}",
+                place_of(&self.golfers[0]),
+                place_of(&self.golfers[1]),
+                self.leaderboard.len(),
+            )?;
+        }
+
+        // A solution's date is when it was submitted, which for each
+        // golfer's best is when they last took anything off.
+
+        if self.show_dates {
+            let date_of = |golfer: &str| self.solutions.iter()
+                .find(|sol| sol.golfer == golfer)
+                .and_then(|sol| sol.submitted_at)
+                .map_or("-".to_string(), |at| at.format("%Y-%m-%d").to_string());
+
+            write!(
+                f, "  {mlgrey}{} vs. {}{reset}",
+                date_of(&self.golfers[0]),
+                date_of(&self.golfers[1]),
+            )?;
+        }
+
+        if self.lead_flipped {
+            write!(f, "  {yellow}{}{reset}", glyphs().lead_flip)?;
+        }
+
+        Ok(())
+    }
+
+    // The label as it goes in the name column, cut short to fit unless
+    // --no-truncate says not to.
+
+    pub fn column_label(&self) -> String {
+        let label = sanitize(self.label());
+        if self.no_truncate {label} else {truncate(&label, self.hole_name_width)}
+    }
+
+    // The score bar, with a marker for each golfer's solution.
+
+    pub fn bar(&self) -> String {
+        let Palette {bold, dim, grey, lgrey, reset, ..} = palette();
+
+        let mut markers: Vec<(String, usize)> = vec![];
+
+        // The --reference golfer is only there for context, so their marker
+        // is drawn faintly, and last, so that it's the one that gets nudged
+        // aside if it lands on someone else's.
+
+        let is_reference = |sol: &&Solution| self.reference.as_ref() == Some(&sol.golfer);
+        let (references, contenders): (Vec<_>, Vec<_>) = self.solutions.iter().partition(is_reference);
+
+        for sol in contenders.into_iter().chain(references) {
+            let (shift, glyph) = self.marker(sol);
+
+            // Anyone who isn't one of the golfers being compared (which
+            // shouldn't happen, but isn't worth crashing over) gets a
+            // neutral grey.
+
+            let color = match self.golfers.iter().position(|i| i == &sol.golfer) {
+                Some(index) => golfer_color(index, self.golfers.len()),
+                None => lgrey.to_string(),
+            };
+
+            let sigil = if is_reference(&sol) {
+                format!("{dim}{lgrey}{glyph}{reset}")
+            } else {
+                format!("{bold}{color}{glyph}{reset}")
+            };
+
+            // If the cell is taken, nudge the marker left into the nearest
+            // free cell, or right if there's no room left of it.
+
+            let shift = (0..=shift).rev().chain(shift+1..)
+                .find(|&cell| !markers.iter().any(|marker| marker.1 == cell))
+                .unwrap();
+
+            markers.push((sigil, shift));
+        }
+
+        (0..self.bar_width).map(|i|
+            markers.iter()
+                   .find(|marker| marker.1 == i)
+                   .map(|marker| marker.0.clone())
+                   .unwrap_or(format!("{grey}{}{reset}", glyphs().rule))
+        ).collect()
+    }
+
+    // Work out which cell of the score bar a solution's marker goes in, and
+    // what glyph to draw there.
+
+    pub fn marker(&self, sol: &Solution) -> (usize, String) {
+        let score = (sol.score * self.bar_scale).min(1000.0);
+
+        let round = |position: f32| match self.bar_rounding {
+            BarRounding::Nearest  => position.round() as usize,
+            BarRounding::Truncate => position as usize,
+        };
+
+        match self.bar_style {
+            BarStyle::Letters => (
+                round(score / 1000.0 * self.bar_width.saturating_sub(1) as f32),
+                // A marker is one cell wide, so an initial that sanitize()
+                // would escape into several (or a login with no initial at
+                // all) is drawn as a "?" instead.
+
+                match sol.golfer.chars().next() {
+                    Some(initial) if !initial.is_control() => initial.to_string(),
+                    _ => "?".to_string(),
+                },
+            ),
+
+            // Split each cell into eighths and draw a partial block whose
+            // right edge sits at the solution's exact position, so scores
+            // that fall in the same cell can still be told apart.
+
+            BarStyle::Blocks => {
+                let eighths = round(score / 1000.0 * (self.bar_width * 8) as f32);
+                let eighths = eighths.max(1);
+
+                (
+                    (eighths - 1) / 8,
+                    glyphs().eighths[(eighths - 1) % 8].to_string(),
+                )
+            }
+        }
+    }
+
+    // The top three on the hole's leaderboard, plus me if I'm not among
+    // them, on one line.
+
+    pub fn mini_board(&self, me: &str) -> String {
+        let Palette {green, mgrey, mlgrey, llgrey, reset, ..} = palette();
+
+        let entry = |sol: &Solution| {
+            let color = if sol.golfer == me {green} else {llgrey};
+            format!("{mlgrey}{}.{reset} {color}{}{reset} {mgrey}{}{reset}", sol.rank.max(1), sanitize(&sol.golfer), sol.length)
+        };
+
+        let mut entries: Vec<String> = self.leaderboard.iter().take(3).map(entry).collect();
+
+        if let Some(mine) = self.leaderboard.iter().skip(3).find(|sol| sol.golfer == me) {
+            entries.push(format!("{mgrey}{}{reset}", glyphs().ellipsis));
+            entries.push(entry(mine));
+        }
+
+        entries.join("  ")
+    }
+}
@@ -0,0 +1,164 @@
+// Drive the processing and formatting end to end from the bundled demo
+// data, the same way --demo does.
+
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+
+use holier_than_thou::model::*;
+use holier_than_thou::process::*;
+use holier_than_thou::render::*;
+
+fn fizz_buzz(cutoff: &str, golfers: &[&str]) -> SolutionLog {
+    let mut logs: HashMap<String, Vec<Solution>> = serde_json::from_str(include_str!("../src/demo/solutions.json")).unwrap();
+    let mut solutions = logs.remove("fizz-buzz").unwrap();
+    fix_up_dates(&mut solutions);
+
+    let golfers: Vec<String> = golfers.iter().map(|golfer| golfer.to_string()).collect();
+    let cutoff_at: DateTime<Utc> = cutoff.parse().unwrap();
+
+    let mut log = SolutionLog {
+        hole_id: "fizz-buzz".to_string(),
+        hole_name: "Fizz Buzz".to_string(),
+        solutions,
+        gold_length: usize::MAX,
+        golfers: golfers.clone(),
+        scoring: "bytes".to_string(),
+        hole_name_width: 12,
+        bar_width: 20,
+        bar_scale: 1.0,
+        ..Default::default()
+    };
+
+    process_log(&mut log, "bytes", cutoff_at, &golfers);
+    log
+}
+
+#[test]
+fn recreates_the_leaderboard_at_the_cutoff() {
+    let log = fizz_buzz("2025-06-01T00:00:00Z", &["alice", "bob"]);
+
+    assert_eq!(log.length_for("alice"), 206);
+    assert_eq!(log.length_for("bob"), 124);
+    assert_eq!(log.gold_length, 107);
+
+    let ranks: Vec<(&str, usize)> = log.leaderboard.iter().map(|sol| (sol.golfer.as_str(), sol.rank)).collect();
+    assert_eq!(ranks, [("carol", 0), ("bob", 2), ("dave", 3), ("erin", 4), ("alice", 5)]);
+}
+
+#[test]
+fn renders_a_hole_line() {
+    PALETTE.set(&PLAIN_PALETTE).ok();
+
+    let log = fizz_buzz("2026-01-01T00:00:00Z", &["alice", "bob"]);
+    let line = log.to_string();
+
+    assert!(line.starts_with("   Fizz Buzz  "), "{line}");
+    assert!(line.ends_with("+87 bytes (206-119|99)"), "{line}");
+}
+
+#[test]
+fn renders_strays_and_empty_logins_without_panicking() {
+    PALETTE.set(&PLAIN_PALETTE).ok();
+
+    let solution = |golfer: &str, length: usize| Solution {
+        bytes: length,
+        chars: length,
+        golfer: golfer.to_string(),
+        scoring: "bytes".to_string(),
+        ..Default::default()
+    };
+
+    let mut log = fizz_buzz("2026-01-01T00:00:00Z", &["alice", "bob"]);
+    log.solutions = vec![solution("alice", 206), solution("", 150), solution("zed", 110)];
+
+    for sol in &mut log.solutions {
+        sol.length = sol.bytes;
+        sol.score = log.gold_length as f32 / sol.length as f32 * 1000.0;
+    }
+
+    let bar = log.bar();
+
+    assert!(bar.contains('?'), "{bar}");
+    assert!(bar.contains('z'), "{bar}");
+    assert!(!log.to_string().is_empty());
+}
+
+#[test]
+fn explains_an_empty_report() {
+    let golfers = ["alice".to_string(), "bob".to_string()];
+
+    assert_eq!(
+        render_no_holes(&golfers, "rust", (0, 0), 0, &[]),
+        "Neither alice nor bob has submitted anything in rust.\n",
+    );
+
+    assert_eq!(
+        render_no_holes(&golfers, "rust", (1, 4), 0, &[]),
+        "No holes where both alice and bob have submitted in rust.\nalice has solved 1 hole on their own, bob 4.\n",
+    );
+
+    assert_eq!(
+        render_no_holes(&golfers, "all", (6, 6), 6, &["--active-within"]),
+        "There were 6 holes to compare in any language, but --active-within left none of them.\n",
+    );
+}
+
+#[test]
+fn escapes_control_characters_in_logins() {
+    PALETTE.set(&PLAIN_PALETTE).ok();
+
+    let evil = "\x1b[2J\rmallory";
+    let mut log = fizz_buzz("2026-01-01T00:00:00Z", &["alice", "bob"]);
+
+    for sol in log.solutions.iter_mut().filter(|sol| sol.golfer == "bob") {
+        sol.golfer = evil.to_string();
+    }
+
+    let golfers = vec!["alice".to_string(), evil.to_string()];
+    log.golfers = golfers.clone();
+
+    let comparison = Comparison {
+        logs: vec![log],
+        golfers,
+        lang: "rust".to_string(),
+        scoring: "bytes".to_string(),
+        scoring_mode: "bytes".to_string(),
+        cutoff: "2026-01-01".to_string(),
+        historical: false,
+        compare_to_self_past: false,
+        near: None,
+        unattempted: None,
+        mini_board: false,
+        links: false,
+        group_by_category: false,
+        summary_format: SummaryFormat::Plain,
+        only: None,
+        min_delta: 0,
+        limit: None,
+        hole_name_width: 33,
+        bar_width: 20,
+    };
+
+    let text = comparison.render_text().unwrap();
+
+    assert!(!text.contains(['\x1b', '\r']), "{text:?}");
+    assert!(text.contains("\\u{1b}[2J\\rmallory"), "{text}");
+
+    // The marker is still a single cell, so the bar keeps its width.
+
+    let bar = comparison.logs[0].bar();
+    assert_eq!(bar.chars().count(), 20, "{bar}");
+    assert!(bar.contains('?'), "{bar}");
+}
+
+#[test]
+fn near_gold_lands_on_the_last_cell_when_rounding() {
+    let log = SolutionLog {bar_width: 20, bar_scale: 1.0, ..Default::default()};
+    let sol = Solution {golfer: "alice".to_string(), score: 999.0, ..Default::default()};
+
+    let nearest = SolutionLog {bar_rounding: BarRounding::Nearest, ..log.clone()};
+    let truncate = SolutionLog {bar_rounding: BarRounding::Truncate, ..log};
+
+    assert_eq!(nearest.marker(&sol).0, 19);
+    assert_eq!(truncate.marker(&sol).0, 18);
+}